@@ -1,22 +1,217 @@
-use crate::character::Character;
+use std::{collections::BTreeMap, rc::Rc, time::Duration};
+
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+
+use crate::{bounds::Bounds, character::{Character, Direction}, entities::{Entities, EntityId}, script::{Behavior, Script}, tilemap::TileMap};
+
+/* Players are drawn over NPCs */
+const PLAYER_Z: f64 = 1.0;
+const NPC_Z: f64 = 0.0;
+const NPC_COLOR: Color = Color::Blue;
+
+/* Something the player asks for, from the keyboard or a script of inputs */
+/* In JSON { "move": "right" }, { "redirect": "up" } or "cancel" */
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Input {
+    Move(Direction),
+    Redirect(Direction),
+    Cancel
+}
 
 pub struct App {
     /* Struct to store data for UI and game logic */
-    pub character: Character,
+    pub entities: Entities,
+    pub player: EntityId, /* The character moved with the keys */
+    pub bounds: Bounds, /* The canvas, no character leaves it */
+    pub map: Option<Rc<TileMap>>, /* Walls and floor, the world is empty without a map */
+    pub behaviors: BTreeMap<EntityId, Behavior>, /* Scripts of the NPCs, NPCs without one stand still */
     pub last_frame_time: u128
 }
 impl App {
     pub fn new() -> App {
+        let bounds = Bounds::new([0.0, 100.0], [0.0, 100.0]); /* Scaling 0 - 100% */
+        let mut entities = Entities::default();
+        let mut player = Character::new();
+        player.set_bounds(Some(bounds));
+        player.position.set_z(PLAYER_Z);
         App {
-            character: Character::new(),
+            player: entities.spawn(player),
+            entities,
+            bounds,
+            map: None,
+            behaviors: BTreeMap::new(),
             last_frame_time: 0u128
         }
     }
 
+    /// # Play on a map
+    /// The canvas shrinks or grows to the map, the player goes to its start tile and an NPC
+    /// is spawned on every NPC tile. NPCs of an earlier map are removed with their scripts
+    pub fn set_map(&mut self, map: TileMap) -> &mut Self {
+        let map = Rc::new(map);
+        let npcs: Vec<EntityId> = self.entities.iter().map(|(id, _)| id).filter(|&id| id != self.player).collect();
+        for id in npcs {
+            self.entities.despawn(id);
+            self.behaviors.remove(&id);
+        }
+        self.bounds = map.bounds();
+        if let Some((column, row)) = map.start() {
+            let z = self.player().position.get_z();
+            self.player_mut().position = *map.center(column, row).set_z(z);
+        }
+        for &(column, row) in map.npc_starts() {
+            let mut npc = Character::new();
+            npc.position = *map.center(column, row).set_z(NPC_Z);
+            npc.color = NPC_COLOR;
+            self.entities.spawn(npc);
+        }
+        for (_, character) in self.entities.iter_mut() {
+            character.set_bounds(Some(self.bounds)).set_map(Some(map.clone()));
+        }
+        self.map = Some(map);
+        self
+    }
+
+    /// # Give the NPCs scripts
+    /// NPCs get the scripts in the order they were spawned, starting over when there are more
+    /// NPCs than scripts. Every NPC gets its own random numbers, all made from `seed`
+    pub fn set_scripts(&mut self, scripts: &[Script], seed: u64) -> &mut Self {
+        self.behaviors.clear();
+        if scripts.is_empty() {
+            return self;
+        }
+        let npcs: Vec<EntityId> = self.entities.iter().map(|(id, _)| id).filter(|&id| id != self.player).collect();
+        for (index, id) in npcs.into_iter().enumerate() {
+            let script = scripts[index % scripts.len()].clone();
+            self.behaviors.insert(id, Behavior::new(script, seed.wrapping_add(index as u64)));
+        }
+        self
+    }
+
+    pub fn player(&self) -> &Character {
+        self.entities.get(self.player).expect("the player is never removed")
+    }
+
+    pub fn player_mut(&mut self) -> &mut Character {
+        self.entities.get_mut(self.player).expect("the player is never removed")
+    }
+
+    /// # One step of the simulation
+    /// Applies the inputs of this step to the player, then advances every entity by `elapsed`.
+    /// Needs no terminal, the main loop and the headless mode both run the world through this
+    pub fn step(&mut self, inputs: &[Input], elapsed: Duration) -> &mut Self {
+        for &input in inputs {
+            self.handle_input(input);
+        }
+        self.update_entities(elapsed)
+    }
+
+    pub fn handle_input(&mut self, input: Input) -> &mut Self {
+        let player = self.player_mut();
+        match input {
+            Input::Move(direction) => player.move_to(direction),
+            Input::Redirect(direction) => player.redirect(direction),
+            Input::Cancel => player.cancel_move(),
+        };
+        self
+    }
+
     /****** HELPER FUNCTIONS ******/
 
-    pub fn update_entities(&mut self, scaler : f64) -> &mut Self {
-        self.character.update(scaler);
+    pub fn update_entities(&mut self, elapsed: Duration) -> &mut Self {
+        /* NPCs that stand still get their next move first, so it plays from this frame on */
+        let player = self.player().position;
+        for (&id, behavior) in self.behaviors.iter_mut() {
+            let Some(npc) = self.entities.get_mut(id) else { continue };
+            if let Some(direction) = behavior.next_move(npc, &player) {
+                npc.move_to(direction);
+            }
+        }
+        self.entities.update(elapsed);
         self
     }
-}
\ No newline at end of file
+}
+
+/*
+   -----
+   TESTS
+   -----
+ */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn map_places_player_and_npcs() {
+        let mut app = App::new();
+        app.set_map(TileMap::from_text("N..\n.#.\n.@N").unwrap());
+
+        let player = app.player();
+        assert_eq!((player.position.get_x(), player.position.get_y(), player.position.get_z()), (15.0, 5.0, PLAYER_Z));
+
+        let npcs: Vec<(f64, f64)> = app.entities.iter()
+            .filter(|&(id, _)| id != app.player)
+            .map(|(_, npc)| (npc.position.get_x(), npc.position.get_y()))
+            .collect();
+        assert_eq!(npcs, [(5.0, 25.0), (25.0, 5.0)]);
+        assert_eq!(app.entities.by_z().last().map(|&(id, _)| id), Some(app.player));
+        assert_eq!(app.bounds, Bounds::new([0.0, 30.0], [0.0, 30.0]));
+    }
+
+    #[test]
+    fn new_map_replaces_the_npcs() {
+        let mut app = App::new();
+        app.set_map(TileMap::from_text("N..\n.@N").unwrap());
+        app.set_scripts(&[Script::RandomWalk], 0);
+        app.set_map(TileMap::from_text("..N\n@..").unwrap());
+
+        let ids: Vec<EntityId> = app.entities.iter().map(|(id, _)| id).collect();
+        assert_eq!(ids.len(), 2);
+        assert_eq!(ids[0], app.player);
+        assert_eq!(app.entities.get(ids[1]).unwrap().position.get_x(), 25.0);
+        assert!(app.behaviors.is_empty());
+    }
+
+    /* Positions of the NPCs after running for some frames */
+    fn npc_positions(seed: u64, frames: u32) -> Vec<(f64, f64)> {
+        let mut app = App::new();
+        app.set_map(TileMap::from_text("N...N\n.....\n..@..\n.....\nN...N").unwrap());
+        app.set_scripts(&[Script::RandomWalk, Script::FollowPlayer, Script::Patrol(vec![Direction::Down])], seed);
+        for _ in 0..frames {
+            app.update_entities(Duration::from_millis(100));
+        }
+        app.entities.iter()
+            .filter(|&(id, _)| id != app.player)
+            .map(|(_, npc)| (npc.position.get_x(), npc.position.get_y()))
+            .collect()
+    }
+
+    #[test]
+    fn scripted_runs_are_reproducible() {
+        assert_eq!(npc_positions(5, 100), npc_positions(5, 100));
+        assert_ne!(npc_positions(5, 100), npc_positions(6, 100));
+
+        let positions = npc_positions(5, 100);
+        /* Following the player ends next to them, the patrol walks into the bottom edge */
+        assert_eq!(positions[1], (25.0, 35.0));
+        assert_eq!(positions[2], (5.0, 5.0));
+    }
+
+    #[test]
+    fn steps_apply_inputs_to_the_player() {
+        let mut app = App::new();
+        app.step(&[Input::Move(Direction::Right), Input::Move(Direction::Up)], Duration::from_millis(800));
+        assert_eq!(app.player().position.get_x(), 15.0);
+        assert!(app.player().is_moving());
+
+        app.step(&[Input::Cancel], Duration::from_millis(100));
+        assert_eq!((app.player().position.get_x(), app.player().position.get_y()), (15.0, 5.0));
+        assert!(!app.player().is_moving());
+
+        app.step(&[Input::Move(Direction::Left), Input::Redirect(Direction::Up)], Duration::from_millis(800));
+        assert_eq!((app.player().position.get_x(), app.player().position.get_y()), (15.0, 15.0));
+    }
+}
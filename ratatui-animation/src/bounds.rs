@@ -0,0 +1,54 @@
+use crate::position::Position;
+
+/* Rectangle the world fits in, in canvas units */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bounds {
+    pub x: [f64; 2], /* left and right edge */
+    pub y: [f64; 2] /* bottom and top edge */
+}
+
+impl Bounds {
+
+    pub fn new(x: [f64; 2], y: [f64; 2]) -> Bounds {
+        Bounds { x, y }
+    }
+
+    /// # Does a box fit
+    /// The box is given by its center and size, touching an edge still fits
+    pub fn fits(&self, center: &Position, width: f64, height: f64) -> bool {
+        const EPSILON: f64 = 1e-9; /* positions are sums of floats */
+        center.get_x() - width / 2.0 >= self.x[0] - EPSILON
+            && center.get_x() + width / 2.0 <= self.x[1] + EPSILON
+            && center.get_y() - height / 2.0 >= self.y[0] - EPSILON
+            && center.get_y() + height / 2.0 <= self.y[1] + EPSILON
+    }
+}
+
+/*
+   -----
+   TESTS
+   -----
+ */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(x: f64, y: f64) -> Position {
+        *Position::new().set_x(x).set_y(y)
+    }
+
+    #[test]
+    fn fits() {
+        let bounds = Bounds::new([0.0, 100.0], [0.0, 50.0]);
+
+        assert!(bounds.fits(&at(5.0, 5.0), 10.0, 10.0));
+        assert!(bounds.fits(&at(95.0, 45.0), 10.0, 10.0));
+        assert!(bounds.fits(&at(0.1 + 0.2 + 4.7, 45.0), 10.0, 10.0));
+        assert!(!bounds.fits(&at(-5.0, 5.0), 10.0, 10.0));
+        assert!(!bounds.fits(&at(96.0, 5.0), 10.0, 10.0));
+        assert!(!bounds.fits(&at(50.0, 4.0), 10.0, 10.0));
+        assert!(!bounds.fits(&at(50.0, 46.0), 10.0, 10.0));
+        assert!(!bounds.fits(&at(50.0, 25.0), 10.0, 60.0));
+    }
+}
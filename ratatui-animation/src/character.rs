@@ -1,60 +1,81 @@
 
-use crate::{frame_data, position::Position};
+use std::{collections::VecDeque, rc::Rc, time::Duration};
+
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+
+use crate::{bounds::Bounds, position::Position, sprite::Sprite, tilemap::TileMap, timeline::{Sample, Timeline}};
 
 #[derive(Default)]
 pub struct Character {
     pub position: Position, /* Position of the origin, in this case the middle of the character (rectangle) */
-    pub speed: f64, // speed in units/event
     pub width: f64,
     pub height: f64,
+    pub color: Color,
+    pub on_blocked: Blocked, /* What a move out of the bounds does */
+    pub sprite: Option<Rc<Sprite>>, /* Drawn instead of a plain rectangle */
 
-    anim_slice: u32, /* current animation slice */
+    idle_elapsed: Duration, /* time since the last animation ended */
+
+    anim_elapsed: Duration, /* time since the current animation started */
 
     anim_start_position: Position,
-    anim_start_speed: f64,
     anim_start_width: f64,
     anim_start_height: f64,
+    anim_start_color: Color,
+
+    anim_type: AnimType,
+    move_timeline: Timeline, /* Played for every move, as if moving right */
+    bump_timeline: Timeline, /* Played for a blocked move */
+    bounds: Option<Bounds>, /* Where the character may go, anywhere if None */
+    map: Option<Rc<TileMap>>, /* Walls the character can not go through */
+    queued_moves: VecDeque<Direction> /* Moves asked for during an animation, started one after the other */
+}
 
-    anim_target_position: Position,
-    anim_target_speed: f64,
-    anim_target_width: f64,
-    anim_target_height: f64,
+/* Direction of a move on the grid */
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right
+}
+
+/* What the character is doing, picks the frame of a sprite */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pose {
+    Idle(Duration), /* standing still for this long */
+    Moving(f64), /* phase of the animation from 0 to 1 */
+    Squash(f64) /* like moving, while the size changes */
+}
 
-    anim_type: AnimType
+/* Reaction to a move that would leave the bounds or run into a wall */
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Blocked {
+    Refuse, /* nothing happens */
+    #[default]
+    Bump /* bumps into the edge and back */
 }
 
 #[derive(Default)]
 #[derive(PartialEq)]
 enum AnimType {
     #[default]
-    NONE,
-    UP,
-    DOWN,
-    LEFT,
-    RIGHT
+    None,
+    Move(Direction),
+    Bump(Direction)
 }
 
 /* Animation pparameters */
-const ANIM_FRAME_SLICES: u32 = 10000; 
-const ANIM_TIME_MOVEMENT_TO_SMALL_MS: u32 = 200;
-const ANIM_TIME_MOVEMENT_SHIFT_MS: u32 = 400;
-const ANIM_TIME_MOVEMENT_TO_BIG_MS: u32 = 200;
-const WIDTH_SMALL: u32 = 3; const HEIGHT_SMALL: u32 = 3;
 const WIDTH_BIG: u32 = 10; const HEIGHT_BIG: u32 = 10;
-const MOVEMENT_UNIT: u32 = WIDTH_BIG; /* Moving on a grid */
-
-/* Deriving commulated time info for further use */
-const ANIM_TIME_FULL_MS: u32 = ANIM_TIME_MOVEMENT_TO_SMALL_MS +
-    ANIM_TIME_MOVEMENT_SHIFT_MS +
-    ANIM_TIME_MOVEMENT_TO_BIG_MS;
-const ANIM_SLICE_TIME_US: u32 = ((ANIM_TIME_FULL_MS as f64 / ANIM_FRAME_SLICES as f64) * 1000.0) as u32; // TODO rounding error
+pub const MOVEMENT_UNIT: u32 = WIDTH_BIG; /* Moving on a grid, also the size of a map tile */
+const MOVE_QUEUE_LEN: usize = 3; /* Moves waiting for the current one, more are dropped */
 
-/* Describing slices for animation */
-const SLICE_FROM_MOVEMENT_TO_SMALL: u32 = 0;
-const SLICE_FROM_MOVEMENT_SHIFT: u32 = ANIM_TIME_MOVEMENT_TO_SMALL_MS as u32 * 1000 / ANIM_SLICE_TIME_US as u32
-    +SLICE_FROM_MOVEMENT_TO_SMALL;
-const SLICE_FROM_MOVEMENT_TO_BIG: u32 = ANIM_TIME_MOVEMENT_SHIFT_MS as u32 * 1000 / ANIM_SLICE_TIME_US as u32
-    +SLICE_FROM_MOVEMENT_SHIFT;
+/* Shrinking, shifting one unit and growing back, see the file for the phase lengths */
+const MOVE_TIMELINE: &str = include_str!("../assets/move.json");
+/* A short push towards the edge and back */
+const BUMP_TIMELINE: &str = include_str!("../assets/bump.json");
 
 impl Character {
 
@@ -62,199 +83,525 @@ impl Character {
     Public methods
     --------------------*/
 
-    pub fn new() -> Character { 
-        let mut character = Character::default();
-        character.speed = 1.0;
-        character.width = WIDTH_BIG as f64;
-        character.height = HEIGHT_BIG as f64;
-        character.anim_type = AnimType::NONE;
+    pub fn new() -> Character {
+        let mut character = Character {
+            width: WIDTH_BIG as f64,
+            height: HEIGHT_BIG as f64,
+            color: Color::Red,
+            anim_type: AnimType::None,
+            move_timeline: Timeline::from_json(MOVE_TIMELINE).expect("assets/move.json is a valid timeline"),
+            bump_timeline: Timeline::from_json(BUMP_TIMELINE).expect("assets/bump.json is a valid timeline"),
+            ..Default::default()
+        };
 
         /* Placing character at the bottom left corner */
         character.position.set_x(character.width/2.0); character.position.set_y(character.width/2.0);
         character
     }
 
-    pub fn move_up(&mut self ) -> &mut Self {
+    /// # Replace the move animation
+    /// The timeline is played for every move, its position track as if moving right
+    pub fn set_move_timeline(&mut self, timeline: Timeline) -> &mut Self {
+        self.move_timeline = timeline;
+        self
+    }
+
+    /// # Keep the character within bounds
+    /// Moves that would leave them are refused or bumped, see `on_blocked`
+    pub fn set_bounds(&mut self, bounds: Option<Bounds>) -> &mut Self {
+        self.bounds = bounds;
+        self
+    }
+
+    /// # Walls to stay out of
+    /// Moves into a wall are refused or bumped like moves out of the bounds
+    pub fn set_map(&mut self, map: Option<Rc<TileMap>>) -> &mut Self {
+        self.map = map;
+        self
+    }
+
+    /// # Move one unit
+    /// Starts at once when standing still, otherwise waits in the queue for the moves before it.
+    /// A move is dropped when the queue is full
+    pub fn move_to(&mut self, direction: Direction) -> &mut Self {
         if !self.anim_in_progress() {
-            self.anim_type = AnimType::UP;
+            self.anim_type = match (self.can_move(direction), self.on_blocked) {
+                (true, _) => AnimType::Move(direction),
+                (false, Blocked::Bump) => AnimType::Bump(direction),
+                (false, Blocked::Refuse) => return self,
+            };
             self.start_animation();
+        } else if self.queued_moves.len() < MOVE_QUEUE_LEN {
+            self.queued_moves.push_back(direction);
         }
         self
     }
-    pub fn move_down(&mut self ) -> &mut Self {
-        //self.position.incr_y(self.speed);
-        if !self.anim_in_progress() {
-            self.anim_type = AnimType::DOWN;
-            self.start_animation();
+
+    /// # Cancel the current move and the queued ones
+    /// The character snaps back to where the current move started, so it stays on the grid
+    pub fn cancel_move(&mut self) -> &mut Self {
+        self.queued_moves.clear();
+        if self.anim_in_progress() {
+            self.position = self.anim_start_position;
+            self.width = self.anim_start_width;
+            self.height = self.anim_start_height;
+            self.color = self.anim_start_color;
+            self.anim_type = AnimType::None;
         }
         self
     }
-    pub fn move_left(&mut self ) -> &mut Self {
-        //self.position.decr_x(self.speed);
+
+    /// # Move in another direction right now
+    /// Cancels the current and queued moves and starts the new one from where the current one started
+    pub fn redirect(&mut self, direction: Direction) -> &mut Self {
+        self.cancel_move().move_to(direction)
+    }
+
+    /// # Is a move or bump playing
+    pub fn is_moving(&self) -> bool {
+        self.anim_in_progress()
+    }
+
+    /// # What the character is doing now
+    /// Moving turns into squash while the timeline changes the size
+    pub fn pose(&self) -> Pose {
         if !self.anim_in_progress() {
-            self.anim_type = AnimType::LEFT;
-            self.start_animation();
+            return Pose::Idle(self.idle_elapsed);
         }
-        self
+        let anim_time_ms = self.anim_elapsed.as_secs_f64() * 1000.0;
+        let full_anim_time = self.timeline().duration_ms() as f64;
+        let phase = if full_anim_time > 0.0 { anim_time_ms / full_anim_time } else { 1.0 };
+        if self.timeline().size_changing(anim_time_ms) {
+            Pose::Squash(phase)
+        } else {
+            Pose::Moving(phase)
+        }
+    }
+
+    /// # Would a move stay within the bounds and out of walls
+    /// Checks the place the character stands on now, not where the current move ends
+    pub fn can_move(&self, direction: Direction) -> bool {
+        let (dx, dy) = direction.vector();
+        let mut target = self.position;
+        target.incr_x(dx * MOVEMENT_UNIT as f64).incr_y(dy * MOVEMENT_UNIT as f64);
+        let fits = self.bounds.is_none_or(|bounds| bounds.fits(&target, WIDTH_BIG as f64, HEIGHT_BIG as f64));
+        fits && !self.map.as_ref().is_some_and(|map| map.is_blocked(&target))
     }
-    pub fn move_right(&mut self ) -> &mut Self {
-        //self.position.incr_x(self.speed);
+
+    /// # Advance the animation
+    /// `elapsed` is the time since the last update, the animation takes the same time at any frame rate
+    pub fn update(&mut self, elapsed: Duration) -> &mut Self {
+
+        /* If no need to animate do not change anything */
         if !self.anim_in_progress() {
-            self.anim_type = AnimType::RIGHT;
-            self.start_animation();
+            self.idle_elapsed += elapsed;
+            return self;
+        }
+
+        /* Running over the end of the animation means we are ready, render the end */
+        let full_anim_time = Duration::from_millis(self.timeline().duration_ms() as u64);
+        let anim_elapsed = self.anim_elapsed + elapsed;
+        self.anim_elapsed = anim_elapsed.min(full_anim_time);
+
+        /* Setting every track to where the timeline is at this time */
+        self.apply_sample(self.timeline().sample(self.anim_elapsed.as_secs_f64() * 1000.0));
+
+        /* Stop the animation if the end was rendered (animation was done) */
+        if self.anim_elapsed == full_anim_time {
+            self.anim_type = AnimType::None;
+
+            /* The next queued move gets the time left over from this one, refused ones are skipped */
+            while let Some(direction) = self.queued_moves.pop_front() {
+                self.move_to(direction);
+                if self.anim_in_progress() {
+                    self.update(anim_elapsed - full_anim_time);
+                    break;
+                }
+            }
         }
         self
     }
 
-    pub fn update(&mut self, scaler: f64) -> &mut Self {
+    fn start_animation(&mut self) {
 
-        /* If no need to animate do not change anything */
-        if !self.anim_in_progress() { return self; }
+        /* Reset the animation time to 0 */
+        self.anim_elapsed = Duration::ZERO;
+        self.idle_elapsed = Duration::ZERO;
 
-        let full_anim_time = ANIM_TIME_MOVEMENT_SHIFT_MS;
-        let anim_slice_time_us: f64 = full_anim_time as f64 / (ANIM_FRAME_SLICES as f64);
-        let target_anim_slice_per_frame = frame_data::TARGET_MSPS as f64 / anim_slice_time_us;
-        let current_frame_num_slices = (target_anim_slice_per_frame.round() * scaler).round() as u32;
+        /* Update informmation at the the start of an animation */
+        self.anim_start_position = self.position;
+        self.anim_start_width = self.width;
+        self.anim_start_height = self.height;
+        self.anim_start_color = self.color;
+    }
 
-        let mut anim_slice_to_render: u32 = self.anim_slice + current_frame_num_slices;
+    /*--------------------
+    Helper methods
+    --------------------*/
 
-        /* Overflow of animation slice counter, meaning we are ready */
-        if anim_slice_to_render > ANIM_FRAME_SLICES { 
-            anim_slice_to_render = ANIM_FRAME_SLICES /* Render the last slice */ 
-        };
+    fn anim_in_progress(&self) -> bool {
+        self.anim_type != AnimType::None
+    }
 
-        if anim_slice_to_render >= SLICE_FROM_MOVEMENT_TO_BIG { /* Animation of transition back to big */
-
-            /* 
-                Setting everything that happened before this animation phase
-            */
-
-            /* Updating postion */
-            self.position = self.anim_target_position.clone();
-
-            /* Calculating phase of current animation section */
-            let anim_phase = calc_anim_phase(SLICE_FROM_MOVEMENT_TO_BIG, ANIM_FRAME_SLICES, anim_slice_to_render);
-
-            /* Updating width and height based on animation section phase */
-            let width = (((WIDTH_BIG - WIDTH_SMALL) as f64 * anim_phase) + WIDTH_SMALL as f64)
-                .round();
-            let height = (((HEIGHT_BIG - HEIGHT_SMALL) as f64 * anim_phase) + HEIGHT_SMALL as f64)
-                .round();
-            self.width = width;
-            self.height = height;
-
-        } else if anim_slice_to_render >= SLICE_FROM_MOVEMENT_SHIFT { /* Animation of movement */
-
-            /* 
-                Setting everything that happened before this animation phase
-            */
-
-            /* Set width and height to small */
-            self.width = WIDTH_SMALL as f64;
-            self.height = WIDTH_BIG as f64;
-
-            /* Calculating phase of current animation section */
-            let anim_phase = calc_anim_phase(SLICE_FROM_MOVEMENT_SHIFT, SLICE_FROM_MOVEMENT_TO_BIG, anim_slice_to_render);
-            
-            /* Calculating movement based on animation section phase */
-            let movement_offset = MOVEMENT_UNIT as f64 * anim_phase;
-            match self.anim_type {
-                AnimType::UP => {
-                    self.position.set_y(self.anim_start_position.get_y() - movement_offset);
-                },
-                AnimType::DOWN => {
-                    self.position.set_y(self.anim_start_position.get_y() + movement_offset);
-                },
-                AnimType::LEFT => {
-                    self.position.set_x(self.anim_start_position.get_x() - movement_offset);
-                },
-                AnimType::RIGHT => {
-                    self.position.set_x(self.anim_start_position.get_x() + movement_offset);
-                },
-                _ => {}
-            }
+    /* Timeline of the current animation */
+    fn timeline(&self) -> &Timeline {
+        match self.anim_type {
+            AnimType::Bump(_) => &self.bump_timeline,
+            _ => &self.move_timeline,
+        }
+    }
+
+    /* Tracks without keyframes leave their property as it is */
+    fn apply_sample(&mut self, sample: Sample) {
+        if let Some([along, across]) = sample.position {
+            /* Turning the offset of a move to the right into the direction of this move */
+            let (dx, dy) = match self.anim_type {
+                AnimType::None => (0.0, 0.0),
+                AnimType::Move(direction) | AnimType::Bump(direction) => direction.vector(),
+            };
+            let unit = MOVEMENT_UNIT as f64;
+            self.position.set_x(self.anim_start_position.get_x() + (along * dx - across * dy) * unit);
+            self.position.set_y(self.anim_start_position.get_y() + (along * dy + across * dx) * unit);
+        }
+        if let Some(width) = sample.width {
+            self.width = width.round();
+        }
+        if let Some(height) = sample.height {
+            self.height = height.round();
+        }
+        if let Some(color) = sample.color {
+            self.color = color;
+        }
+    }
+}
+
+impl Direction {
+    /* Unit vector of the direction, y grows upwards on the canvas */
+    fn vector(self) -> (f64, f64) {
+        match self {
+            Direction::Up => (0.0, 1.0),
+            Direction::Down => (0.0, -1.0),
+            Direction::Left => (-1.0, 0.0),
+            Direction::Right => (1.0, 0.0),
+        }
+    }
+}
+
+/*
+   -----
+   TESTS
+   -----
+ */
 
-        } else if anim_slice_to_render >= SLICE_FROM_MOVEMENT_TO_SMALL { /* Animation of transition to small */
-            let anim_phase = calc_anim_phase(SLICE_FROM_MOVEMENT_TO_SMALL, SLICE_FROM_MOVEMENT_SHIFT, anim_slice_to_render);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-            /* Updating width and height */
-            let width = (((WIDTH_BIG - WIDTH_SMALL) as f64 * (1.0-anim_phase) ) + WIDTH_SMALL as f64)
-                .round();
-            let height = (((HEIGHT_BIG - HEIGHT_SMALL) as f64 * (1.0-anim_phase) ) + WIDTH_SMALL as f64)
-                .round();
-            self.width = width;
-            self.height = height;
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    /* Updates `steps` times with the same frame time */
+    fn run(character: &mut Character, steps: u32, frame: Duration) {
+        for _ in 0..steps {
+            character.update(frame);
         }
+    }
 
-        /* Update enimation slice so next iteration knows where we are */
-        self.anim_slice = anim_slice_to_render;
-        
-        /* Stop the animation if last slice was rendered (animation was done) */
-        if anim_slice_to_render == ANIM_FRAME_SLICES {
-            self.anim_type = AnimType::NONE;
+    fn xy(character: &Character) -> (f64, f64) {
+        (character.position.get_x(), character.position.get_y())
+    }
+
+    #[test]
+    fn moves_one_unit_and_returns_to_full_size() {
+        let mut character = Character::new();
+        character.move_to(Direction::Right);
+        character.update(ms(16));
+        assert!(character.width < WIDTH_BIG as f64);
+
+        run(&mut character, 100, ms(16));
+        assert!(!character.anim_in_progress());
+        assert_eq!(xy(&character), (15.0, 5.0));
+        assert_eq!((character.width, character.height), (10.0, 10.0));
+
+        character.move_to(Direction::Up);
+        run(&mut character, 100, ms(16));
+        assert_eq!(xy(&character), (15.0, 15.0));
+    }
+
+    #[test]
+    fn phases_of_the_move() {
+        let mut character = Character::new();
+        character.move_to(Direction::Left);
+
+        /* Shrunk, not moved yet */
+        character.update(ms(200));
+        assert_eq!((character.width, character.height), (3.0, 3.0));
+        assert_eq!(xy(&character), (5.0, 5.0));
+
+        /* Half way through the shift */
+        character.update(ms(200));
+        assert_eq!(xy(&character), (0.0, 5.0));
+
+        /* Shifted, growing back */
+        character.update(ms(200));
+        assert_eq!(xy(&character), (-5.0, 5.0));
+        assert_eq!(character.width, 3.0);
+        character.update(ms(199));
+        assert!(character.anim_in_progress());
+        character.update(ms(1));
+        assert!(!character.anim_in_progress());
+        assert_eq!(character.width, 10.0);
+    }
+
+    #[test]
+    fn length_does_not_depend_on_the_frame_rate() {
+        for frame in [ms(1), ms(5), ms(8), ms(40), ms(100)] {
+            let mut character = Character::new();
+            character.move_to(Direction::Down);
+            run(&mut character, (800 / frame.as_millis()) as u32 - 1, frame);
+            assert!(character.anim_in_progress(), "finished early at {frame:?} per frame");
+            character.update(frame);
+            assert!(!character.anim_in_progress(), "not finished at {frame:?} per frame");
         }
-        self
+
+        /* The same time in different steps gives the same state */
+        let (mut slow, mut fast) = (Character::new(), Character::new());
+        slow.move_to(Direction::Right);
+        fast.move_to(Direction::Right);
+        run(&mut slow, 3, ms(110));
+        run(&mut fast, 66, ms(5));
+        fast.update(Duration::ZERO);
+        assert_eq!(xy(&slow), xy(&fast));
+        assert_eq!((slow.width, slow.height), (fast.width, fast.height));
     }
 
-    fn start_animation(&mut self) -> () {
+    #[test]
+    fn long_frames_finish_the_move() {
+        let mut character = Character::new();
+        character.move_to(Direction::Right);
+        character.update(Duration::from_secs(5));
 
-        /* Reset current animation slice to 0 */
-        self.anim_slice = 0;
+        assert!(!character.anim_in_progress());
+        assert_eq!(xy(&character), (15.0, 5.0));
+    }
 
-        /* Update informmation at the the start of an animation */
-        self.anim_start_height = self.height;
-        self.anim_start_width = self.width;
-        self.anim_start_speed = self.speed;
-        self.anim_start_position.set_x(self.position.get_x());
-        self.anim_start_position.set_y(self.position.get_y());
-        self.anim_start_position.set_z(self.position.get_z());
+    #[test]
+    fn position_track_is_turned_into_the_direction_of_the_move() {
+        let timeline = Timeline::from_json(r#"{
+            "position": [{ "duration_ms": 0, "value": [0, 0] }, { "duration_ms": 100, "value": [1, 0.5] }],
+            "color": [{ "duration_ms": 0, "value": [0, 0, 0] }, { "duration_ms": 100, "value": [0, 200, 0] }]
+        }"#).unwrap();
+        let mut character = Character::new();
+        character.set_move_timeline(timeline);
+
+        character.move_to(Direction::Down);
+        character.update(ms(100));
+        assert_eq!(xy(&character), (10.0, -5.0));
+        assert_eq!(character.color, Color::Rgb(0, 200, 0));
+        /* No size tracks, the size is left alone */
+        assert_eq!((character.width, character.height), (10.0, 10.0));
+
+        character.move_to(Direction::Left);
+        character.update(ms(100));
+        assert_eq!(xy(&character), (0.0, -10.0));
+    }
 
-        /* Update information at the end of the animation */
-        self.anim_target_height = self.height;
-        self.anim_target_width = self.width;
-        self.anim_target_speed = self.speed;
+    #[test]
+    fn empty_timeline_finishes_at_once() {
+        let mut character = Character::new();
+        character.set_move_timeline(Timeline::default());
+        character.move_to(Direction::Right);
+        character.update(Duration::ZERO);
 
-        // This calculation is wrong TODO
-        match self.anim_type {
-            AnimType::UP => {
-                self.anim_target_position.set_y(self.anim_start_position.get_y() - MOVEMENT_UNIT as f64);
-            },
-            AnimType::DOWN => {
-                self.anim_target_position.set_y(self.anim_start_position.get_y() + MOVEMENT_UNIT as f64);
-            },
-            AnimType::LEFT => {
-                self.anim_target_position.set_x(self.anim_start_position.get_x() - MOVEMENT_UNIT as f64);
-            },
-            AnimType::RIGHT => {
-                self.anim_target_position.set_x(self.anim_start_position.get_x() + MOVEMENT_UNIT as f64);
-            },
-            _ => {}
+        assert!(!character.anim_in_progress());
+        assert_eq!(character.position.get_x(), 5.0);
+    }
+
+    #[test]
+    fn moves_during_an_animation_are_queued() {
+        let mut character = Character::new();
+        character.move_to(Direction::Right);
+        character.update(ms(100));
+        character.move_to(Direction::Up).move_to(Direction::Right);
+        assert_eq!(character.queued_moves, [Direction::Up, Direction::Right]);
+
+        /* Each queued move starts when the one before ends */
+        character.update(ms(700));
+        assert_eq!(xy(&character), (15.0, 5.0));
+        assert!(character.anim_in_progress());
+        assert_eq!(character.queued_moves, [Direction::Right]);
+
+        run(&mut character, 2, ms(800));
+        assert_eq!(xy(&character), (25.0, 15.0));
+        assert!(!character.anim_in_progress());
+        assert!(character.queued_moves.is_empty());
+    }
+
+    #[test]
+    fn left_over_time_goes_to_the_next_move() {
+        let mut character = Character::new();
+        character.move_to(Direction::Right).move_to(Direction::Right).move_to(Direction::Down);
+
+        /* Two and a half moves in one long frame */
+        character.update(ms(2000));
+        assert_eq!(xy(&character), (25.0, 0.0));
+        character.update(ms(400));
+        assert_eq!(xy(&character), (25.0, -5.0));
+        assert!(!character.anim_in_progress());
+    }
+
+    #[test]
+    fn full_queue_drops_moves() {
+        let mut character = Character::new();
+        character.move_to(Direction::Right);
+        for _ in 0..10 {
+            character.move_to(Direction::Down);
         }
+        assert_eq!(character.queued_moves.len(), MOVE_QUEUE_LEN);
 
-        ()
+        character.update(Duration::from_secs(60));
+        assert_eq!(xy(&character), (15.0, 5.0 - MOVE_QUEUE_LEN as f64 * 10.0));
     }
 
-    /*--------------------
-    Helper methods
-    --------------------*/
+    #[test]
+    fn cancel_returns_to_the_start_of_the_move() {
+        let mut character = Character::new();
+        character.move_to(Direction::Right).move_to(Direction::Up);
+        character.update(ms(500));
+        assert_ne!(xy(&character), (5.0, 5.0));
+
+        character.cancel_move();
+        assert!(!character.anim_in_progress());
+        assert!(character.queued_moves.is_empty());
+        assert_eq!(xy(&character), (5.0, 5.0));
+        assert_eq!((character.width, character.height), (10.0, 10.0));
+
+        /* Nothing left to play */
+        character.update(ms(800));
+        assert_eq!(xy(&character), (5.0, 5.0));
+        /* Standing still there is nothing to cancel */
+        character.cancel_move();
+        assert_eq!(xy(&character), (5.0, 5.0));
+    }
 
-    fn anim_in_progress(&self) -> bool {
-        self.anim_type != AnimType::NONE
+    #[test]
+    fn redirect_replaces_the_current_move() {
+        let mut character = Character::new();
+        character.move_to(Direction::Right).move_to(Direction::Right);
+        character.update(ms(300));
+
+        character.redirect(Direction::Up);
+        assert!(character.queued_moves.is_empty());
+        character.update(ms(800));
+        assert_eq!(xy(&character), (5.0, 15.0));
+        assert!(!character.anim_in_progress());
     }
 
+    /* A character in a 30x20 world at a cell, cells are 10 units */
+    fn bounded_at(column: u32, row: u32) -> Character {
+        let mut character = Character::new();
+        character.set_bounds(Some(Bounds::new([0.0, 30.0], [0.0, 20.0])));
+        character.position.set_x(column as f64 * 10.0 + 5.0).set_y(row as f64 * 10.0 + 5.0);
+        character
+    }
 
-}
+    #[test]
+    fn every_edge_bumps() {
+        for (column, row, blocked) in [(0, 0, Direction::Left), (2, 1, Direction::Right), (1, 0, Direction::Down), (1, 1, Direction::Up)] {
+            let mut character = bounded_at(column, row);
+            let start = xy(&character);
+            character.move_to(blocked);
+            assert!(character.anim_type == AnimType::Bump(blocked), "{blocked:?} from {start:?} is not bumped");
+
+            /* Pushed towards the edge, then back where it was */
+            character.update(ms(100));
+            assert_ne!(xy(&character), start);
+            character.update(ms(1000));
+            assert!(!character.anim_in_progress());
+            assert_eq!(xy(&character), start);
+        }
+    }
 
-/*--------------------
-Public functions
---------------------*/
+    #[test]
+    fn every_edge_refuses() {
+        for (column, row, blocked) in [(0, 1, Direction::Left), (2, 0, Direction::Right), (0, 0, Direction::Down), (2, 1, Direction::Up)] {
+            let mut character = bounded_at(column, row);
+            character.on_blocked = Blocked::Refuse;
+            let start = xy(&character);
+            character.move_to(blocked);
+            assert!(!character.anim_in_progress(), "{blocked:?} from {start:?} is not refused");
+            character.update(ms(1000));
+            assert_eq!(xy(&character), start);
+        }
+    }
 
-//
+    #[test]
+    fn moves_within_bounds_are_allowed() {
+        let mut character = bounded_at(0, 0);
+        for direction in [Direction::Right, Direction::Right, Direction::Up, Direction::Left, Direction::Down] {
+            character.move_to(direction);
+            assert!(character.anim_type == AnimType::Move(direction));
+            character.update(ms(800));
+        }
+        assert_eq!(xy(&character), (15.0, 5.0));
+    }
 
-/*--------------------
-Helper functions
---------------------*/
+    #[test]
+    fn refused_queued_moves_are_skipped() {
+        let mut character = bounded_at(1, 0);
+        character.on_blocked = Blocked::Refuse;
+        character.move_to(Direction::Left).move_to(Direction::Left).move_to(Direction::Up);
 
-fn calc_anim_phase(start: u32, end: u32, current: u32) -> f64 {
-    (current - start) as f64 / (end - start) as f64
-}
\ No newline at end of file
+        character.update(ms(800 + 800));
+        assert!(!character.anim_in_progress());
+        assert_eq!(xy(&character), (5.0, 15.0));
+    }
+
+    #[test]
+    fn walls_block_moves() {
+        /* Row 0 is the top, the character starts in the bottom left corner */
+        let map = TileMap::from_text("...\n.#.\n@..").unwrap();
+        let mut character = Character::new();
+        character.set_map(Some(Rc::new(map)));
+
+        character.move_to(Direction::Right);
+        character.update(ms(800));
+        assert_eq!(xy(&character), (15.0, 5.0));
+
+        /* The wall is above */
+        character.move_to(Direction::Up);
+        assert!(character.anim_type == AnimType::Bump(Direction::Up));
+        character.update(ms(800));
+        assert_eq!(xy(&character), (15.0, 5.0));
+
+        /* Around the wall, the edge of the map is a wall too */
+        for direction in [Direction::Right, Direction::Up, Direction::Up, Direction::Left] {
+            character.move_to(direction);
+            character.update(ms(800));
+        }
+        assert_eq!(xy(&character), (15.0, 25.0));
+        character.move_to(Direction::Up);
+        assert!(character.anim_type == AnimType::Bump(Direction::Up));
+    }
+
+    #[test]
+    fn poses() {
+        let mut character = Character::new();
+        character.update(ms(300));
+        assert_eq!(character.pose(), Pose::Idle(ms(300)));
+
+        character.move_to(Direction::Right);
+        character.update(ms(100));
+        assert_eq!(character.pose(), Pose::Squash(0.125));
+        character.update(ms(300));
+        assert_eq!(character.pose(), Pose::Moving(0.5));
+        character.update(ms(300));
+        assert!(matches!(character.pose(), Pose::Squash(_)));
+
+        /* Idle time starts over after a move */
+        character.update(ms(100));
+        character.update(ms(50));
+        assert_eq!(character.pose(), Pose::Idle(ms(50)));
+    }
+}
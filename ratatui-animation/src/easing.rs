@@ -0,0 +1,213 @@
+use std::f64::consts::PI;
+
+use serde::{Deserialize, Serialize};
+
+/* Overshoot of the back curves */
+const BACK: f64 = 1.70158;
+const BACK_IN_OUT: f64 = BACK * 1.525;
+
+/* Shape of a transition, maps the linear phase of a segment to the eased one */
+/* In JSON a name like "cubic_in_out", or { "cubic_bezier": [x1, y1, x2, y2] } */
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Easing {
+    #[default]
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    ExpoIn,
+    ExpoOut,
+    ExpoInOut,
+    BackIn, /* pulls back below 0 before starting */
+    BackOut, /* overshoots 1 before settling */
+    BackInOut,
+    ElasticIn,
+    ElasticOut,
+    ElasticInOut,
+    BounceIn,
+    BounceOut,
+    BounceInOut,
+    CubicBezier([f64; 4]) /* control points (x1, y1) and (x2, y2) like in CSS, x is kept within 0..1 */
+}
+
+impl Easing {
+
+    /// # Eased phase
+    /// `phase` is clamped to 0..1, every curve starts at 0 and ends at 1
+    pub fn ease(self, phase: f64) -> f64 {
+        let t = phase.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::QuadIn => t * t,
+            Easing::QuadOut => 1.0 - (1.0 - t).powi(2),
+            Easing::QuadInOut => in_out(t, |t| t * t),
+            Easing::CubicIn => t.powi(3),
+            Easing::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Easing::CubicInOut => in_out(t, |t| t.powi(3)),
+            Easing::ExpoIn => expo_in(t),
+            Easing::ExpoOut => 1.0 - expo_in(1.0 - t),
+            Easing::ExpoInOut => in_out(t, expo_in),
+            Easing::BackIn => back_in(t, BACK),
+            Easing::BackOut => 1.0 - back_in(1.0 - t, BACK),
+            Easing::BackInOut => in_out(t, |t| back_in(t, BACK_IN_OUT)),
+            Easing::ElasticIn => elastic_in(t),
+            Easing::ElasticOut => 1.0 - elastic_in(1.0 - t),
+            Easing::ElasticInOut => in_out(t, elastic_in),
+            Easing::BounceIn => 1.0 - bounce_out(1.0 - t),
+            Easing::BounceOut => bounce_out(t),
+            Easing::BounceInOut => in_out(t, |t| 1.0 - bounce_out(1.0 - t)),
+            Easing::CubicBezier([x1, y1, x2, y2]) => cubic_bezier(t, x1.clamp(0.0, 1.0), y1, x2.clamp(0.0, 1.0), y2),
+        }
+    }
+}
+
+/*--------------------
+Helper functions
+--------------------*/
+
+/* The in curve for the first half, mirrored for the second */
+fn in_out(t: f64, ease_in: impl Fn(f64) -> f64) -> f64 {
+    if t < 0.5 {
+        ease_in(t * 2.0) / 2.0
+    } else {
+        1.0 - ease_in((1.0 - t) * 2.0) / 2.0
+    }
+}
+
+fn expo_in(t: f64) -> f64 {
+    if t == 0.0 { 0.0 } else { 2f64.powf(10.0 * t - 10.0) }
+}
+
+fn back_in(t: f64, overshoot: f64) -> f64 {
+    (overshoot + 1.0) * t.powi(3) - overshoot * t * t
+}
+
+fn elastic_in(t: f64) -> f64 {
+    if t == 0.0 || t == 1.0 {
+        return t;
+    }
+    -2f64.powf(10.0 * t - 10.0) * ((10.0 * t - 10.75) * 2.0 * PI / 3.0).sin()
+}
+
+fn bounce_out(t: f64) -> f64 {
+    const N: f64 = 7.5625;
+    const D: f64 = 2.75;
+    if t < 1.0 / D {
+        N * t * t
+    } else if t < 2.0 / D {
+        let t = t - 1.5 / D;
+        N * t * t + 0.75
+    } else if t < 2.5 / D {
+        let t = t - 2.25 / D;
+        N * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D;
+        N * t * t + 0.984375
+    }
+}
+
+/* Finds the curve parameter where x is `t` by bisection, x grows with it as x1 and x2 are within 0..1 */
+fn cubic_bezier(t: f64, x1: f64, y1: f64, x2: f64, y2: f64) -> f64 {
+    let bezier = |a: f64, b: f64, s: f64| 3.0 * a * s * (1.0 - s).powi(2) + 3.0 * b * s * s * (1.0 - s) + s.powi(3);
+    let (mut low, mut high) = (0.0, 1.0);
+    for _ in 0..60 {
+        let middle = (low + high) / 2.0;
+        if bezier(x1, x2, middle) < t {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    bezier(y1, y2, (low + high) / 2.0)
+}
+
+/*
+   -----
+   TESTS
+   -----
+ */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [Easing; 20] = [
+        Easing::Linear,
+        Easing::QuadIn, Easing::QuadOut, Easing::QuadInOut,
+        Easing::CubicIn, Easing::CubicOut, Easing::CubicInOut,
+        Easing::ExpoIn, Easing::ExpoOut, Easing::ExpoInOut,
+        Easing::BackIn, Easing::BackOut, Easing::BackInOut,
+        Easing::ElasticIn, Easing::ElasticOut, Easing::ElasticInOut,
+        Easing::BounceIn, Easing::BounceOut, Easing::BounceInOut,
+        Easing::CubicBezier([0.25, 0.1, 0.25, 1.0]),
+    ];
+
+    /* Curves that never go back, back and elastic overshoot and bounce bounces */
+    const MONOTONIC: [Easing; 12] = [
+        Easing::Linear,
+        Easing::QuadIn, Easing::QuadOut, Easing::QuadInOut,
+        Easing::CubicIn, Easing::CubicOut, Easing::CubicInOut,
+        Easing::ExpoIn, Easing::ExpoOut, Easing::ExpoInOut,
+        Easing::CubicBezier([0.25, 0.1, 0.25, 1.0]),
+        Easing::CubicBezier([0.9, 0.0, 0.1, 1.0]),
+    ];
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    #[test]
+    fn endpoints() {
+        for easing in ALL {
+            assert!(close(easing.ease(0.0), 0.0), "{easing:?} starts at {}", easing.ease(0.0));
+            assert!(close(easing.ease(1.0), 1.0), "{easing:?} ends at {}", easing.ease(1.0));
+            /* Outside 0..1 is clamped */
+            assert_eq!(easing.ease(-0.5), easing.ease(0.0));
+            assert_eq!(easing.ease(7.0), easing.ease(1.0));
+        }
+    }
+
+    #[test]
+    fn monotonic() {
+        for easing in MONOTONIC {
+            let mut last = easing.ease(0.0);
+            for step in 1..=1000 {
+                let value = easing.ease(step as f64 / 1000.0);
+                assert!(value >= last - 1e-9, "{easing:?} goes back at step {step}");
+                last = value;
+            }
+        }
+    }
+
+    #[test]
+    fn in_out_curves_are_symmetric() {
+        for easing in [Easing::QuadInOut, Easing::CubicInOut, Easing::ExpoInOut, Easing::BackInOut, Easing::ElasticInOut, Easing::BounceInOut] {
+            assert!(close(easing.ease(0.5), 0.5), "{easing:?}");
+            assert!(close(easing.ease(0.2), 1.0 - easing.ease(0.8)), "{easing:?}");
+        }
+    }
+
+    #[test]
+    fn shapes() {
+        assert!(close(Easing::QuadIn.ease(0.5), 0.25));
+        assert!(close(Easing::CubicOut.ease(0.5), 0.875));
+        assert!(Easing::BackIn.ease(0.2) < 0.0);
+        assert!(Easing::BackOut.ease(0.8) > 1.0);
+        assert!(Easing::ElasticOut.ease(0.2) > 1.0);
+        assert!(close(Easing::BounceOut.ease(1.0 / 2.75), 1.0));
+        /* A straight bezier is linear */
+        assert!(close(Easing::CubicBezier([0.3, 0.3, 0.7, 0.7]).ease(0.37), 0.37));
+        assert!(close(Easing::CubicBezier([0.42, 0.0, 0.58, 1.0]).ease(0.5), 0.5));
+    }
+
+    #[test]
+    fn from_json() {
+        let easings: Vec<Easing> = serde_json::from_str(r#"["linear", "bounce_in_out", { "cubic_bezier": [0.1, 0.7, 1.0, 0.1] }]"#).unwrap();
+        assert_eq!(easings, [Easing::Linear, Easing::BounceInOut, Easing::CubicBezier([0.1, 0.7, 1.0, 0.1])]);
+        assert!(serde_json::from_str::<Easing>(r#""wobble""#).is_err());
+    }
+}
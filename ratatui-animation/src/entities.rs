@@ -0,0 +1,132 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::character::Character;
+
+/* Handle of an entity, never reused once the entity is gone */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub struct EntityId(u32);
+
+/* Every animated character of the world, each animates on its own */
+#[derive(Default)]
+pub struct Entities {
+    next_id: u32,
+    characters: BTreeMap<EntityId, Character> /* ordered by id, so in the order they were spawned */
+}
+
+impl Entities {
+
+    /// # Add a character
+    /// Returns the id it can be found by from now on
+    pub fn spawn(&mut self, character: Character) -> EntityId {
+        let id = EntityId(self.next_id);
+        self.next_id += 1;
+        self.characters.insert(id, character);
+        id
+    }
+
+    /// # Remove a character
+    /// Its id is not given out again, None if there is no character with that id
+    pub fn despawn(&mut self, id: EntityId) -> Option<Character> {
+        self.characters.remove(&id)
+    }
+
+    pub fn get(&self, id: EntityId) -> Option<&Character> {
+        self.characters.get(&id)
+    }
+
+    pub fn get_mut(&mut self, id: EntityId) -> Option<&mut Character> {
+        self.characters.get_mut(&id)
+    }
+
+    /// # Every character with its id
+    /// In the order they were spawned
+    pub fn iter(&self) -> impl Iterator<Item = (EntityId, &Character)> {
+        self.characters.iter().map(|(&id, character)| (id, character))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (EntityId, &mut Character)> {
+        self.characters.iter_mut().map(|(&id, character)| (id, character))
+    }
+
+    /// # Advance every animation
+    pub fn update(&mut self, elapsed: Duration) -> &mut Self {
+        for character in self.characters.values_mut() {
+            character.update(elapsed);
+        }
+        self
+    }
+
+    /// # Characters in the order to draw them
+    /// Lowest `z` first, so higher ones are drawn over them. Equal `z` keeps the spawn order
+    pub fn by_z(&self) -> Vec<(EntityId, &Character)> {
+        let mut characters: Vec<_> = self.iter().collect();
+        characters.sort_by(|(_, a), (_, b)| a.position.get_z().total_cmp(&b.position.get_z()));
+        characters
+    }
+}
+
+/*
+   -----
+   TESTS
+   -----
+ */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::character::Direction;
+
+    fn at_z(z: f64) -> Character {
+        let mut character = Character::new();
+        character.position.set_z(z);
+        character
+    }
+
+    #[test]
+    fn ids_are_stable() {
+        let mut entities = Entities::default();
+        let first = entities.spawn(at_z(0.0));
+        let second = entities.spawn(at_z(1.0));
+        assert_ne!(first, second);
+
+        assert_eq!(entities.despawn(first).unwrap().position.get_z(), 0.0);
+        assert!(entities.despawn(first).is_none());
+        let third = entities.spawn(at_z(2.0));
+        assert!(third != first && third != second);
+        assert_eq!(entities.get(second).unwrap().position.get_z(), 1.0);
+        assert!(entities.get(first).is_none());
+        assert_eq!(entities.iter().map(|(id, _)| id).collect::<Vec<_>>(), [second, third]);
+    }
+
+    #[test]
+    fn characters_animate_independently() {
+        let mut entities = Entities::default();
+        let mover = entities.spawn(Character::new());
+        let idle = entities.spawn(Character::new());
+        entities.get_mut(mover).unwrap().move_to(Direction::Right);
+        entities.update(Duration::from_millis(400));
+
+        let late = entities.spawn(Character::new());
+        entities.get_mut(late).unwrap().move_to(Direction::Up);
+        entities.update(Duration::from_millis(400));
+
+        assert_eq!(entities.get(mover).unwrap().position.get_x(), 15.0);
+        assert_eq!(entities.get(idle).unwrap().position.get_x(), 5.0);
+        assert_eq!(entities.get(late).unwrap().position.get_y(), 10.0);
+    }
+
+    #[test]
+    fn draw_order_follows_z() {
+        let mut entities = Entities::default();
+        let top = entities.spawn(at_z(2.0));
+        let bottom = entities.spawn(at_z(-1.0));
+        let middle = entities.spawn(at_z(0.0));
+        let also_middle = entities.spawn(at_z(0.0));
+
+        let order: Vec<EntityId> = entities.by_z().into_iter().map(|(id, _)| id).collect();
+        assert_eq!(order, [bottom, middle, also_middle, top]);
+    }
+}
@@ -1,13 +1,12 @@
-use std::time::{Instant};
+use std::time::{Duration, Instant};
 
 pub static TARGET_FPS : u128 = 60; 
-pub static TARGET_MSPS: u128 = ((1.0 / TARGET_FPS as f64) * 1000.0 ) as u128;
 pub static TARGET_USPS: u128 = ((1.0 / TARGET_FPS as f64) * 1000.0 * 1000.0 ) as u128;
 
 pub struct FrameData {
     frame_number: u128,
     last_frame_timestamp: Instant,
-    last_frame_time_us: u128,
+    last_frame_time: Duration,
     scale: f64,
 }
 
@@ -20,7 +19,7 @@ impl FrameData {
         let mut fd = FrameData {
             frame_number: (0),
             last_frame_timestamp: Instant::now(),
-            last_frame_time_us: (0),
+            last_frame_time: Duration::ZERO,
             scale: (1.0)
         };
 
@@ -34,12 +33,12 @@ impl FrameData {
     /// parameters to support FPS independent operation
     pub fn update(&mut self) -> &Self {
         self.frame_number += 1;
-        self.last_frame_time_us = self.last_frame_timestamp
-            .elapsed()
-            .as_micros();
-        self.last_frame_timestamp = Instant::now();
-        self.scale = (self.last_frame_time_us as f64 / TARGET_USPS as f64)
-            .clamp(1.0, 100.0); // TODO decide max
+        let now = Instant::now();
+        self.last_frame_time = now - self.last_frame_timestamp;
+        self.last_frame_timestamp = now;
+        /* Below 1.0 when running faster than the target */
+        self.scale = (self.last_frame_time_us() as f64 / TARGET_USPS as f64)
+            .min(100.0); // TODO decide max
 
         self
     }
@@ -56,17 +55,23 @@ impl FrameData {
         (*self.get_scale()) * (*quantity)
     }
 
+    /// # Last Frame time
+    /// Returns the exact time between the last two update calls, use it to advance animations
+    pub fn last_frame_time(&self) -> Duration {
+        self.last_frame_time
+    }
+
     /// # Last Frame time in microseconds
     /// Returns the frame time duration before the last update call
     pub fn last_frame_time_us(&self) -> u128 {
-        self.last_frame_time_us
+        self.last_frame_time.as_micros()
     }
 
     // TODO not great here!
     /// # Target time to sleep to meet target FPS
     /// Return time to sleep
     pub fn target_tsleep_us(&self) -> u128 {
-        (TARGET_USPS as i128 - self.last_frame_time_us as i128).clamp(0, 1000 * 1000) as u128 // TODO decide max
+        (TARGET_USPS as i128 - self.last_frame_time_us() as i128).clamp(0, 1000 * 1000) as u128 // TODO decide max
     }
 }
 
@@ -1,55 +1,48 @@
 use std::{error::Error};
 use std::time::{Duration};
 use ratatui::crossterm::{
-        event::{self, Event, KeyCode},
+        event::{self, Event, KeyCode, KeyModifiers},
         
     };
 
-use crate::app::App;
+use crate::{app::Input, character::Direction};
 
-/// # Call this function to handle user inputs, user events
-/// 
-pub fn handle_inputs(app: &mut App) -> Result<bool, Box<dyn Error>> {
+/// # Call this function to read user inputs, user events
+/// Returns the inputs of this frame for the simulation and whether the user asked to quit
+pub fn read_inputs() -> Result<(Vec<Input>, bool), Box<dyn Error>> {
 
-    let events: Option<Vec<Event>> = collect_events()?;
+    let mut inputs = Vec::new();
 
-    /* If we have no event, simple return */
-    if let None = events { return Ok(false) } 
-
-    /* From this point, it is sure that we have valid events */
-    let events: Vec<Event> = events.unwrap();
-
-    /* Do what needs to be done at specific events */
-    for event in events.iter() {
+    /* Turn the events of this frame into inputs */
+    for event in collect_events()?.iter() {
         if let Event::Key(key) = event { 
             if key.kind == event::KeyEventKind::Release {
-                return Ok(false);
-            }
-            match key.code {
-                KeyCode::Left =>  { app.character.move_left(); }
-                KeyCode::Right => { app.character.move_right(); }
-                KeyCode::Up => { app.character.move_down(); } // TODO naming
-                KeyCode::Down => { app.character.move_up(); } // TODO naming
-                KeyCode::Char('q') => { return Ok(true); }
-                _ => {}
+                continue;
             }
+            /* Shift turns a move into a redirect of the current one */
+            let redirect = key.modifiers.contains(KeyModifiers::SHIFT);
+            let direction = match key.code {
+                KeyCode::Left => Direction::Left,
+                KeyCode::Right => Direction::Right,
+                KeyCode::Up => Direction::Up,
+                KeyCode::Down => Direction::Down,
+                KeyCode::Esc => { inputs.push(Input::Cancel); continue; }
+                KeyCode::Char('q') => { return Ok((inputs, true)); }
+                _ => continue,
+            };
+            inputs.push(if redirect { Input::Redirect(direction) } else { Input::Move(direction) });
         }
     }
-    Ok(false)
+    Ok((inputs, false))
 }
 
 /// # Returns a vector containing the events in this frame
 /// Return vector contains all the events that heppened during this frame
-fn collect_events() -> Result<Option<Vec<Event>>, Box<dyn Error>> {
+fn collect_events() -> Result<Vec<Event>, Box<dyn Error>> {
     let mut events: Vec<event::Event> = Vec::new();
     while event::poll(Duration::from_millis(0))? {
         let event = event::read()?;
         events.push(event);
     }
-
-    if events.len() == 0 {
-        return Ok(None);
-    }
-
-    Ok(Some(events))
-}
\ No newline at end of file
+    Ok(events)
+}
@@ -1,4 +1,4 @@
-use std::{error::Error, thread, time};
+use std::{env, error::Error, io, path::Path, rc::Rc, thread, time::{self, Duration, SystemTime, UNIX_EPOCH}};
 
 use ratatui::{
     backend::{Backend},
@@ -6,24 +6,97 @@ use ratatui::{
 };
 
 mod app;
+mod entities;
+mod bounds;
 mod ui;
 mod character;
 mod position;
 mod terminal;
 mod frame_data;
 mod inputs;
+mod simulation;
+mod sprite;
+mod script;
+mod tilemap;
+mod easing;
+mod timeline;
 
 use crate::{
-    app::App, frame_data::FrameData, ui::ui
+    app::App, character::Blocked, frame_data::FrameData, script::Script, sprite::Sprite, tilemap::TileMap, timeline::Timeline, ui::ui
 };
 
 fn main() -> Result<(), Box<dyn Error>> {
 
+    /* Create app, reading the arguments before the terminal is taken over so errors stay readable */
+    let mut app = App::new();
+    let mut scripts = Vec::new();
+    let mut seed = None;
+    let mut headless_steps = None;
+    let mut step_time = Duration::from_micros(frame_data::TARGET_USPS as u64);
+    let mut timed_inputs = Vec::new();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--move-timeline" => {
+                let path = args.next().ok_or("--move-timeline needs a JSON file")?;
+                app.player_mut().set_move_timeline(Timeline::load(Path::new(&path))?);
+            }
+            "--map" => {
+                let path = args.next().ok_or("--map needs a text or JSON map file")?;
+                app.set_map(TileMap::load(Path::new(&path))?);
+            }
+            "--scripts" => {
+                let path = args.next().ok_or("--scripts needs a JSON file")?;
+                scripts = Script::load_all(Path::new(&path))?;
+            }
+            "--seed" => {
+                let number = args.next().ok_or("--seed needs a number")?;
+                seed = Some(number.parse().map_err(|_| format!("--seed needs a number, got '{number}'"))?);
+            }
+            "--sprite" => {
+                let path = args.next().ok_or("--sprite needs a JSON file")?;
+                app.player_mut().sprite = Some(Rc::new(Sprite::load(Path::new(&path))?));
+            }
+            "--headless" => {
+                let number = args.next().ok_or("--headless needs a number of steps")?;
+                headless_steps = Some(number.parse().map_err(|_| format!("--headless needs a number of steps, got '{number}'"))?);
+            }
+            "--step-ms" => {
+                let number = args.next().ok_or("--step-ms needs a number")?;
+                step_time = Duration::from_millis(number.parse().map_err(|_| format!("--step-ms needs a number, got '{number}'"))?);
+            }
+            "--inputs" => {
+                let path = args.next().ok_or("--inputs needs a JSON file")?;
+                timed_inputs = simulation::load_inputs(Path::new(&path))?;
+            }
+            "--blocked" => {
+                app.player_mut().on_blocked = match args.next().as_deref() {
+                    Some("bump") => Blocked::Bump,
+                    Some("refuse") => Blocked::Refuse,
+                    _ => return Err("--blocked needs bump or refuse".into()),
+                };
+            }
+            _ => return Err(format!("unknown argument '{arg}', use --map FILE, --scripts FILE, --seed N, --sprite FILE, --move-timeline FILE, --blocked bump|refuse, --headless STEPS, --step-ms MS or --inputs FILE").into()),
+        }
+    }
+
+    /* Scripts go to the NPCs of the map, so they are set up once all arguments are read */
+    /* Headless runs are meant to be compared, so they do not take a random seed */
+    let seed = seed.unwrap_or_else(|| match headless_steps {
+        Some(_) => 0,
+        None => SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since| since.as_nanos() as u64),
+    });
+    app.set_scripts(&scripts, seed);
+
+    /* Without a terminal every step goes to stdout as a JSON line */
+    if let Some(steps) = headless_steps {
+        return simulation::run(&mut app, steps, step_time, &timed_inputs, &mut io::stdout().lock());
+    }
+
     /* itit terminal for ui */
     let mut terminal = terminal::init_terminal()?;
 
-    /* Create app and run it  */
-    let mut app = App::new();
+    /* Run the app */
     run_app(&mut terminal, &mut app)?;
 
     /* Restore the terminal to the state we were starting with */
@@ -43,13 +116,14 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Result<(),
         /* Update FrameData to provide information of last frame duration */
         fd.update();
 
+        // Handling interaction
+        let (inputs, quit) = inputs::read_inputs()?;
+        stop = quit;
+
         /* Update the whole application */
-        app.update_entities(*fd.get_scale());
+        app.step(&inputs, fd.last_frame_time());
         app.last_frame_time = fd.last_frame_time_us();
 
-        // Handling interaction
-        stop = inputs::handle_inputs(app)?;
-
         // Draw the UI, render
         terminal.draw(|f| ui(f, app))?;
 
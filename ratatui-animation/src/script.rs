@@ -0,0 +1,206 @@
+use std::{error::Error, fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{character::{Character, Direction, MOVEMENT_UNIT}, position::Position};
+
+const DIRECTIONS: [Direction; 4] = [Direction::Up, Direction::Down, Direction::Left, Direction::Right];
+
+/* How an NPC moves, every script only ever makes the usual one unit moves */
+/* In JSON { "patrol": ["right", "right", "left", "left"] }, "random_walk" or "follow_player" */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Script {
+    Patrol(Vec<Direction>), /* the moves one after the other, over and over */
+    RandomWalk, /* a random free direction each time */
+    FollowPlayer /* towards the player until next to them */
+}
+
+/* A script running for one NPC */
+pub struct Behavior {
+    script: Script,
+    step: usize, /* next move of a patrol */
+    rng: Rng
+}
+
+/* Xorshift generator, the same seed always gives the same numbers */
+pub struct Rng(u64);
+
+impl Script {
+
+    /// # Load scripts from a JSON file
+    /// The file holds a list of scripts, like `[{ "patrol": ["up", "down"] }, "random_walk"]`
+    pub fn load_all(path: &Path) -> Result<Vec<Script>, Box<dyn Error>> {
+        let json = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&json).map_err(|err| format!("{}: {err}", path.display()))?)
+    }
+}
+
+impl Behavior {
+
+    pub fn new(script: Script, seed: u64) -> Behavior {
+        Behavior { script, step: 0, rng: Rng::new(seed) }
+    }
+
+    /// # The move to make now
+    /// Only asks for a move when the NPC stands still, None if it should keep standing
+    pub fn next_move(&mut self, npc: &Character, player: &Position) -> Option<Direction> {
+        if npc.is_moving() {
+            return None;
+        }
+        match &self.script {
+            Script::Patrol(moves) if moves.is_empty() => None,
+            Script::Patrol(moves) => {
+                let direction = moves[self.step % moves.len()];
+                self.step += 1;
+                Some(direction)
+            }
+            Script::RandomWalk => {
+                let free: Vec<Direction> = DIRECTIONS.into_iter().filter(|&direction| npc.can_move(direction)).collect();
+                (!free.is_empty()).then(|| free[self.rng.below(free.len())])
+            }
+            Script::FollowPlayer => follow(npc, player),
+        }
+    }
+}
+
+impl Rng {
+
+    pub fn new(seed: u64) -> Rng {
+        /* Mixing the seed so close seeds give unrelated numbers, xorshift must not start at 0 */
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        Rng((z ^ (z >> 31)).max(1))
+    }
+
+    pub fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// # A number from 0 to `n` - 1
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+/*--------------------
+Helper functions
+--------------------*/
+
+/* The longer way first, the other way if that is blocked */
+fn follow(npc: &Character, player: &Position) -> Option<Direction> {
+    let dx = player.get_x() - npc.position.get_x();
+    let dy = player.get_y() - npc.position.get_y();
+    if dx.abs() + dy.abs() <= MOVEMENT_UNIT as f64 {
+        return None; /* next to the player already */
+    }
+    let horizontal = (dx != 0.0).then_some(if dx > 0.0 { Direction::Right } else { Direction::Left });
+    let vertical = (dy != 0.0).then_some(if dy > 0.0 { Direction::Up } else { Direction::Down });
+    let ways = if dx.abs() >= dy.abs() { [horizontal, vertical] } else { [vertical, horizontal] };
+    ways.into_iter().flatten().find(|&direction| npc.can_move(direction))
+}
+
+/*
+   -----
+   TESTS
+   -----
+ */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{rc::Rc, time::Duration};
+    use crate::tilemap::TileMap;
+
+    /* Runs a script for `moves` full moves */
+    fn run(behavior: &mut Behavior, npc: &mut Character, player: &Position, moves: usize) -> Vec<(f64, f64)> {
+        (0..moves)
+            .map(|_| {
+                if let Some(direction) = behavior.next_move(npc, player) {
+                    npc.move_to(direction);
+                }
+                npc.update(Duration::from_secs(1));
+                (npc.position.get_x(), npc.position.get_y())
+            })
+            .collect()
+    }
+
+    fn on_map(map: &str) -> Character {
+        let map = TileMap::from_text(map).unwrap();
+        let mut npc = Character::new();
+        npc.position = map.center(0, map.height() - 1);
+        npc.set_bounds(Some(map.bounds())).set_map(Some(Rc::new(map)));
+        npc
+    }
+
+    #[test]
+    fn from_json() {
+        let scripts: Vec<Script> = serde_json::from_str(r#"[{ "patrol": ["up", "left"] }, "random_walk", "follow_player"]"#).unwrap();
+        assert_eq!(scripts, [Script::Patrol(vec![Direction::Up, Direction::Left]), Script::RandomWalk, Script::FollowPlayer]);
+        assert!(serde_json::from_str::<Script>(r#""dance""#).is_err());
+    }
+
+    #[test]
+    fn patrol_repeats() {
+        let mut npc = Character::new();
+        let mut behavior = Behavior::new(Script::Patrol(vec![Direction::Right, Direction::Right, Direction::Left]), 0);
+
+        let path = run(&mut behavior, &mut npc, &Position::new(), 6);
+        assert_eq!(path, [(15.0, 5.0), (25.0, 5.0), (15.0, 5.0), (25.0, 5.0), (35.0, 5.0), (25.0, 5.0)]);
+        assert_eq!(Behavior::new(Script::Patrol(Vec::new()), 0).next_move(&npc, &Position::new()), None);
+    }
+
+    #[test]
+    fn no_new_move_while_moving() {
+        let mut npc = Character::new();
+        let mut behavior = Behavior::new(Script::Patrol(vec![Direction::Up]), 0);
+        npc.move_to(Direction::Right);
+
+        assert_eq!(behavior.next_move(&npc, &Position::new()), None);
+        npc.update(Duration::from_secs(1));
+        assert_eq!(behavior.next_move(&npc, &Position::new()), Some(Direction::Up));
+    }
+
+    #[test]
+    fn random_walk_is_reproducible() {
+        let map = ".....\n.....\n.....\n.....\n.....";
+        let walk = |seed| run(&mut Behavior::new(Script::RandomWalk, seed), &mut on_map(map), &Position::new(), 30);
+
+        assert_eq!(walk(7), walk(7));
+        assert_ne!(walk(7), walk(8));
+        /* Never runs into the edge */
+        assert!(walk(3).iter().all(|&(x, y)| (5.0..=45.0).contains(&x) && (5.0..=45.0).contains(&y)));
+    }
+
+    #[test]
+    fn random_walk_only_takes_free_ways() {
+        /* The only way out is up */
+        let mut npc = on_map(".#\n.#");
+        let path = run(&mut Behavior::new(Script::RandomWalk, 1), &mut npc, &Position::new(), 2);
+        assert_eq!(path[0], (5.0, 15.0));
+    }
+
+    #[test]
+    fn follows_the_player_around_walls() {
+        let mut npc = on_map("....\n.##.\n....");
+        let player = *Position::new().set_x(35.0).set_y(25.0);
+
+        let path = run(&mut Behavior::new(Script::FollowPlayer, 0), &mut npc, &player, 6);
+        /* Right first as that is the longer way, up where the wall ends, then stops next to the player */
+        assert_eq!(path, [(15.0, 5.0), (25.0, 5.0), (35.0, 5.0), (35.0, 15.0), (35.0, 15.0), (35.0, 15.0)]);
+    }
+
+    #[test]
+    fn rng() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+        let numbers: Vec<u64> = (0..5).map(|_| a.next()).collect();
+        assert_eq!(numbers, (0..5).map(|_| b.next()).collect::<Vec<_>>());
+        assert_ne!(Rng::new(0).next(), 0);
+        assert!((0..100).all(|_| a.below(3) < 3));
+    }
+}
@@ -0,0 +1,126 @@
+use std::{error::Error, fs, io::Write, path::Path, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{app::{App, Input}, entities::EntityId};
+
+/* An input given to the simulation right before a step */
+/* In JSON { "step": 3, "input": { "move": "right" } } */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimedInput {
+    pub step: u32,
+    pub input: Input
+}
+
+/* One line of the output, the world after a step */
+#[derive(Serialize)]
+struct StepState {
+    step: u32,
+    entities: Vec<EntityState>
+}
+
+#[derive(Serialize)]
+struct EntityState {
+    id: EntityId,
+    x: f64,
+    y: f64,
+    z: f64,
+    width: f64,
+    height: f64
+}
+
+/// # Load the inputs of a run from a JSON file
+/// A list like `[{ "step": 0, "input": { "move": "right" } }, { "step": 9, "input": "cancel" }]`
+pub fn load_inputs(path: &Path) -> Result<Vec<TimedInput>, Box<dyn Error>> {
+    let json = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&json).map_err(|err| format!("{}: {err}", path.display()))?)
+}
+
+/// # Run the app without a terminal
+/// Steps are numbered from 0, every step gets its inputs and then `step_time`. After each
+/// step one JSON line with every entity is written to `out`, so runs can be compared line by line
+pub fn run(app: &mut App, steps: u32, step_time: Duration, inputs: &[TimedInput], out: &mut impl Write) -> Result<(), Box<dyn Error>> {
+    for step in 0..steps {
+        let step_inputs: Vec<Input> = inputs.iter().filter(|timed| timed.step == step).map(|timed| timed.input).collect();
+        app.step(&step_inputs, step_time);
+
+        let state = StepState {
+            step,
+            entities: app.entities.iter()
+                .map(|(id, character)| EntityState {
+                    id,
+                    x: character.position.get_x(),
+                    y: character.position.get_y(),
+                    z: character.position.get_z(),
+                    width: character.width,
+                    height: character.height,
+                })
+                .collect(),
+        };
+        serde_json::to_writer(&mut *out, &state)?;
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+/*
+   -----
+   TESTS
+   -----
+ */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{character::Direction, script::Script, tilemap::TileMap};
+
+    fn lines(app: &mut App, steps: u32, step_time: Duration, inputs: &[TimedInput]) -> Vec<String> {
+        let mut out = Vec::new();
+        run(app, steps, step_time, inputs, &mut out).unwrap();
+        String::from_utf8(out).unwrap().lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn one_line_per_step() {
+        let inputs = [TimedInput { step: 1, input: Input::Move(Direction::Right) }];
+        let lines = lines(&mut App::new(), 3, Duration::from_millis(400), &inputs);
+
+        assert_eq!(lines, [
+            r#"{"step":0,"entities":[{"id":0,"x":5.0,"y":5.0,"z":1.0,"width":10.0,"height":10.0}]}"#,
+            r#"{"step":1,"entities":[{"id":0,"x":10.0,"y":5.0,"z":1.0,"width":3.0,"height":3.0}]}"#,
+            r#"{"step":2,"entities":[{"id":0,"x":15.0,"y":5.0,"z":1.0,"width":10.0,"height":10.0}]}"#,
+        ]);
+    }
+
+    #[test]
+    fn inputs_from_json() {
+        let inputs: Vec<TimedInput> = serde_json::from_str(r#"[
+            { "step": 0, "input": { "move": "up" } },
+            { "step": 4, "input": { "redirect": "left" } },
+            { "step": 5, "input": "cancel" }
+        ]"#).unwrap();
+
+        assert_eq!(inputs, [
+            TimedInput { step: 0, input: Input::Move(Direction::Up) },
+            TimedInput { step: 4, input: Input::Redirect(Direction::Left) },
+            TimedInput { step: 5, input: Input::Cancel },
+        ]);
+    }
+
+    /* The shipped level, scripts and inputs against a recorded run, after a wanted change record it again with
+       cargo run -- --map assets/level.txt --scripts assets/npcs.json --seed 1 --inputs assets/inputs.json --step-ms 100 --headless 60 > golden/level.jsonl */
+    #[test]
+    fn golden_level_run() {
+        let mut app = App::new();
+        app.set_map(TileMap::load(Path::new("assets/level.txt")).unwrap());
+        app.set_scripts(&Script::load_all(Path::new("assets/npcs.json")).unwrap(), 1);
+        let inputs = load_inputs(Path::new("assets/inputs.json")).unwrap();
+
+        let lines = lines(&mut app, 60, Duration::from_millis(100), &inputs);
+        let golden: Vec<&str> = include_str!("../golden/level.jsonl").lines().collect();
+        assert_eq!(lines.len(), golden.len());
+        for (line, expected) in lines.iter().zip(golden) {
+            assert_eq!(line, expected);
+        }
+    }
+}
@@ -0,0 +1,258 @@
+use std::{collections::HashMap, error::Error, fs, path::Path, str::FromStr};
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Color,
+};
+use serde::Deserialize;
+
+use crate::character::Pose;
+
+/* One picture of a sprite, a grid of terminal cells */
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpriteFrame {
+    width: u16,
+    height: u16,
+    cells: Vec<(char, Option<Color>)> /* row by row, None takes the color of the character */
+}
+
+/* Frames for everything a character does, frames are picked by the pose of the character */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sprite {
+    frame_ms: u64, /* how long an idle frame is shown */
+    idle: Vec<SpriteFrame>,
+    moving: Vec<SpriteFrame>,
+    squash: Vec<SpriteFrame>
+}
+
+/* The file form of a sprite */
+#[derive(Deserialize)]
+struct SpriteFile {
+    #[serde(default)]
+    palette: HashMap<char, String>, /* color names like "red" or "#ffcc00" */
+    #[serde(default = "default_frame_ms")]
+    frame_ms: u64,
+    idle: Vec<FrameFile>,
+    #[serde(default)]
+    moving: Vec<FrameFile>,
+    #[serde(default)]
+    squash: Vec<FrameFile>
+}
+
+#[derive(Deserialize)]
+struct FrameFile {
+    rows: Vec<String>,
+    #[serde(default)]
+    colors: Vec<String> /* palette letters over the rows, space or missing for the character color */
+}
+
+fn default_frame_ms() -> u64 {
+    500
+}
+
+impl Sprite {
+
+    /// # Parse a sprite from JSON
+    /// `idle` needs at least one frame, `moving` and `squash` fall back to the idle frames
+    pub fn from_json(json: &str) -> Result<Sprite, String> {
+        let file: SpriteFile = serde_json::from_str(json).map_err(|err| err.to_string())?;
+        let palette = file.palette
+            .iter()
+            .map(|(&key, name)| Color::from_str(name).map(|color| (key, color)).map_err(|_| format!("unknown color '{name}' for '{key}'")))
+            .collect::<Result<HashMap<char, Color>, String>>()?;
+        let frames = |frames: &[FrameFile], name: &str| {
+            frames.iter()
+                .enumerate()
+                .map(|(index, frame)| SpriteFrame::new(frame, &palette).map_err(|err| format!("{name} frame {}: {err}", index + 1)))
+                .collect::<Result<Vec<SpriteFrame>, String>>()
+        };
+
+        let idle = frames(&file.idle, "idle")?;
+        if idle.is_empty() {
+            return Err("a sprite needs at least one idle frame".to_string());
+        }
+        let moving = Some(frames(&file.moving, "moving")?).filter(|frames| !frames.is_empty()).unwrap_or_else(|| idle.clone());
+        let squash = Some(frames(&file.squash, "squash")?).filter(|frames| !frames.is_empty()).unwrap_or_else(|| idle.clone());
+        Ok(Sprite { frame_ms: file.frame_ms.max(1), idle, moving, squash })
+    }
+
+    /// # Load a sprite from a JSON file
+    pub fn load(path: &Path) -> Result<Sprite, Box<dyn Error>> {
+        let json = fs::read_to_string(path)?;
+        Ok(Sprite::from_json(&json).map_err(|err| format!("{}: {err}", path.display()))?)
+    }
+
+    /// # Frame for a pose
+    /// Idle frames take turns every `frame_ms`, moving and squash frames are spread over the phase
+    pub fn frame(&self, pose: Pose) -> &SpriteFrame {
+        let through = |frames: &[SpriteFrame], phase: f64| ((phase * frames.len() as f64) as usize).min(frames.len() - 1);
+        match pose {
+            Pose::Idle(idle) => &self.idle[(idle.as_millis() / self.frame_ms as u128) as usize % self.idle.len()],
+            Pose::Moving(phase) => &self.moving[through(&self.moving, phase)],
+            Pose::Squash(phase) => &self.squash[through(&self.squash, phase)],
+        }
+    }
+}
+
+impl SpriteFrame {
+
+    fn new(frame: &FrameFile, palette: &HashMap<char, Color>) -> Result<SpriteFrame, String> {
+        let width = frame.rows.iter().map(|row| row.chars().count()).max().unwrap_or(0);
+        if width == 0 {
+            return Err("the frame is empty".to_string());
+        }
+        let mut cells = Vec::new();
+        for (row, text) in frame.rows.iter().enumerate() {
+            let mut glyphs = text.chars();
+            let mut colors = frame.colors.get(row).map_or("", String::as_str).chars();
+            for _ in 0..width {
+                let color = match colors.next() {
+                    None | Some(' ') => None,
+                    Some(key) => Some(*palette.get(&key).ok_or_else(|| format!("'{key}' is not in the palette"))?),
+                };
+                cells.push((glyphs.next().unwrap_or(' '), color));
+            }
+        }
+        Ok(SpriteFrame { width: width as u16, height: frame.rows.len() as u16, cells })
+    }
+
+    /// # Draw the frame as large as it fits
+    /// Keeps the shape of the frame, terminal cells being taller than wide is in the frame
+    /// already. Centered in the area, spaces are left out so what is behind shows through
+    pub fn render(&self, area: Rect, buf: &mut Buffer, color: Color) {
+        if area.is_empty() {
+            return;
+        }
+        let scale = (area.width as f64 / self.width as f64).min(area.height as f64 / self.height as f64);
+        let width = ((self.width as f64 * scale).round() as u16).clamp(1, area.width);
+        let height = ((self.height as f64 * scale).round() as u16).clamp(1, area.height);
+        let x = area.x + (area.width - width) / 2;
+        let y = area.y + (area.height - height) / 2;
+
+        for row in 0..height {
+            for column in 0..width {
+                /* Nearest cell of the frame */
+                let source_row = (row as u32 * self.height as u32 / height as u32) as usize;
+                let source_column = (column as u32 * self.width as u32 / width as u32) as usize;
+                let (glyph, cell_color) = self.cells[source_row * self.width as usize + source_column];
+                if glyph != ' ' {
+                    buf[(x + column, y + row)].set_char(glyph).set_fg(cell_color.unwrap_or(color));
+                }
+            }
+        }
+    }
+}
+
+/*
+   -----
+   TESTS
+   -----
+ */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    const SPRITE: &str = r##"{
+        "palette": { "r": "red", "y": "#ffcc00" },
+        "frame_ms": 200,
+        "idle": [
+            { "rows": ["/oo\\", "|__|"], "colors": ["yrry"] },
+            { "rows": ["/--\\", "|__|"] }
+        ],
+        "moving": [{ "rows": [">>"] }, { "rows": ["=="] }, { "rows": ["<<"] }]
+    }"##;
+
+    fn glyphs(frame: &SpriteFrame) -> String {
+        frame.cells.iter().map(|&(glyph, _)| glyph).collect()
+    }
+
+    #[test]
+    fn from_json() {
+        let sprite = Sprite::from_json(SPRITE).unwrap();
+
+        let first = &sprite.idle[0];
+        assert_eq!((first.width, first.height), (4, 2));
+        assert_eq!(first.cells[0], ('/', Some(Color::Rgb(255, 204, 0))));
+        assert_eq!(first.cells[1], ('o', Some(Color::Red)));
+        /* No colors for the second row */
+        assert_eq!(first.cells[4], ('|', None));
+        /* Squash falls back to idle */
+        assert_eq!(sprite.squash, sprite.idle);
+    }
+
+    #[test]
+    fn shipped_sprite_loads() {
+        let sprite = Sprite::load(Path::new("assets/player.json")).unwrap();
+        assert_eq!((sprite.idle.len(), sprite.moving.len(), sprite.squash.len()), (2, 2, 1));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(Sprite::from_json(r#"{ "idle": [] }"#), Err("a sprite needs at least one idle frame".to_string()));
+        assert_eq!(Sprite::from_json(r#"{ "idle": [{ "rows": [] }] }"#), Err("idle frame 1: the frame is empty".to_string()));
+        assert_eq!(
+            Sprite::from_json(r#"{ "idle": [{ "rows": ["x"] }], "squash": [{ "rows": ["ab"], "colors": ["gg"] }] }"#),
+            Err("squash frame 1: 'g' is not in the palette".to_string())
+        );
+        assert_eq!(
+            Sprite::from_json(r#"{ "palette": { "g": "greenish" }, "idle": [{ "rows": ["x"] }] }"#),
+            Err("unknown color 'greenish' for 'g'".to_string())
+        );
+    }
+
+    #[test]
+    fn frames_by_pose() {
+        let sprite = Sprite::from_json(SPRITE).unwrap();
+        let idle = |ms| glyphs(sprite.frame(Pose::Idle(Duration::from_millis(ms))));
+
+        assert_eq!(idle(0), "/oo\\|__|");
+        assert_eq!(idle(199), "/oo\\|__|");
+        assert_eq!(idle(200), "/--\\|__|");
+        assert_eq!(idle(400), "/oo\\|__|");
+
+        assert_eq!(glyphs(sprite.frame(Pose::Moving(0.0))), ">>");
+        assert_eq!(glyphs(sprite.frame(Pose::Moving(0.5))), "==");
+        assert_eq!(glyphs(sprite.frame(Pose::Moving(1.0))), "<<");
+        assert_eq!(glyphs(sprite.frame(Pose::Squash(0.9))), "/--\\|__|");
+    }
+
+    #[test]
+    fn render_keeps_the_shape() {
+        let sprite = Sprite::from_json(SPRITE).unwrap();
+        let mut buf = Buffer::empty(Rect::new(0, 0, 10, 4));
+        sprite.idle[1].render(buf.area, &mut buf, Color::Blue);
+
+        /* Twice the size, the extra width stays empty */
+        assert_eq!(buf, {
+            let mut expected = Buffer::with_lines([
+                " //----\\\\ ",
+                " //----\\\\ ",
+                " ||____|| ",
+                " ||____|| ",
+            ]);
+            expected.set_style(Rect::new(1, 0, 8, 4), Color::Blue);
+            expected
+        });
+    }
+
+    #[test]
+    fn render_small_and_transparent() {
+        let sprite = Sprite::from_json(SPRITE).unwrap();
+        let mut buf = Buffer::with_lines(["......"]);
+        sprite.idle[0].render(Rect::new(1, 0, 4, 1), &mut buf, Color::Blue);
+
+        /* Half the height, every other cell */
+        assert_eq!(buf[(1, 0)].symbol(), ".");
+        assert_eq!(buf[(2, 0)].symbol(), "/");
+        assert_eq!(buf[(3, 0)].symbol(), "o");
+        assert_eq!(buf[(4, 0)].symbol(), ".");
+
+        /* Spaces leave the buffer alone */
+        let mut buf = Buffer::with_lines(["...."]);
+        Sprite::from_json(r#"{ "idle": [{ "rows": ["a  b"] }] }"#).unwrap().idle[0].render(buf.area, &mut buf, Color::Reset);
+        assert_eq!(buf, Buffer::with_lines(["a..b"]));
+    }
+}
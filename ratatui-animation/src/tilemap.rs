@@ -0,0 +1,229 @@
+use std::{error::Error, fs, path::Path};
+
+use serde::Deserialize;
+
+use crate::{bounds::Bounds, character::MOVEMENT_UNIT, position::Position};
+
+/* One cell of the map */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tile {
+    Floor,
+    Wall
+}
+
+/* A level made of square tiles, one movement unit wide, row 0 is the top row */
+#[derive(Debug, Clone, PartialEq)]
+pub struct TileMap {
+    width: u32,
+    height: u32,
+    tiles: Vec<Tile>, /* row by row */
+    start: Option<(u32, u32)>, /* column and row marked with '@' */
+    npc_starts: Vec<(u32, u32)> /* columns and rows marked with 'N' */
+}
+
+/* The JSON form of a map, the same rows as in a text map */
+#[derive(Deserialize)]
+struct MapFile {
+    rows: Vec<String>
+}
+
+impl TileMap {
+
+    /// # Parse a text map
+    /// `#` is a wall, `.` or a space is floor, `@` is floor the player starts on and `N` is
+    /// floor an NPC starts on. Short rows are filled up with floor
+    pub fn from_text(text: &str) -> Result<TileMap, String> {
+        let rows: Vec<&str> = text.lines().map(|line| line.trim_end_matches('\r')).collect();
+        TileMap::from_rows(&rows)
+    }
+
+    /// # Parse a JSON map
+    /// `{ "rows": ["#####", "#@..#", "#####"] }`, rows as in a text map
+    pub fn from_json(json: &str) -> Result<TileMap, String> {
+        let file: MapFile = serde_json::from_str(json).map_err(|err| err.to_string())?;
+        TileMap::from_rows(&file.rows)
+    }
+
+    /// # Load a map file
+    /// Files ending in `.json` are JSON maps, anything else is a text map
+    pub fn load(path: &Path) -> Result<TileMap, Box<dyn Error>> {
+        let content = fs::read_to_string(path)?;
+        let map = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => TileMap::from_json(&content),
+            _ => TileMap::from_text(&content),
+        };
+        Ok(map.map_err(|err| format!("{}: {err}", path.display()))?)
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// # Tile at a column and row
+    /// Everything outside the map is wall
+    pub fn tile(&self, column: i64, row: i64) -> Tile {
+        if column < 0 || row < 0 || column >= self.width as i64 || row >= self.height as i64 {
+            return Tile::Wall;
+        }
+        self.tiles[(row * self.width as i64 + column) as usize]
+    }
+
+    /// # Can a position be stood on
+    /// False for walls and everything outside the map
+    pub fn is_blocked(&self, position: &Position) -> bool {
+        let unit = MOVEMENT_UNIT as f64;
+        let column = (position.get_x() / unit).floor() as i64;
+        let row = self.height as i64 - 1 - (position.get_y() / unit).floor() as i64;
+        self.tile(column, row) == Tile::Wall
+    }
+
+    /// # The whole map in canvas units
+    pub fn bounds(&self) -> Bounds {
+        let unit = MOVEMENT_UNIT as f64;
+        Bounds::new([0.0, self.width as f64 * unit], [0.0, self.height as f64 * unit])
+    }
+
+    /// # Center of a tile in canvas units
+    /// The canvas y grows upwards, so the top row has the largest y
+    pub fn center(&self, column: u32, row: u32) -> Position {
+        let unit = MOVEMENT_UNIT as f64;
+        let mut position = Position::new();
+        position.set_x((column as f64 + 0.5) * unit);
+        position.set_y((self.height as f64 - row as f64 - 0.5) * unit);
+        position
+    }
+
+    /// # Where the player starts
+    /// The tile marked with `@`, or the bottom left floor tile if there is none
+    pub fn start(&self) -> Option<(u32, u32)> {
+        self.start.or_else(|| {
+            (0..self.height).rev()
+                .flat_map(|row| (0..self.width).map(move |column| (column, row)))
+                .find(|&(column, row)| self.tile(column as i64, row as i64) == Tile::Floor)
+        })
+    }
+
+    /// # Where NPCs start
+    /// Tiles marked with `N`, row by row
+    pub fn npc_starts(&self) -> &[(u32, u32)] {
+        &self.npc_starts
+    }
+
+    /// # Every tile with its column and row
+    pub fn tiles(&self) -> impl Iterator<Item = (u32, u32, Tile)> + '_ {
+        self.tiles.iter().enumerate().map(|(index, &tile)| (index as u32 % self.width, index as u32 / self.width, tile))
+    }
+
+    /*--------------------
+    Helper methods
+    --------------------*/
+
+    fn from_rows<S: AsRef<str>>(rows: &[S]) -> Result<TileMap, String> {
+        /* Empty lines at the end of a file are not rows */
+        let rows = &rows[..rows.iter().rposition(|row| !row.as_ref().is_empty()).map_or(0, |last| last + 1)];
+        let width = rows.iter().map(|row| row.as_ref().chars().count()).max().unwrap_or(0);
+        if width == 0 {
+            return Err("the map is empty".to_string());
+        }
+
+        let mut map = TileMap { width: width as u32, height: rows.len() as u32, tiles: Vec::new(), start: None, npc_starts: Vec::new() };
+        for (row, line) in rows.iter().enumerate() {
+            let mut chars = line.as_ref().chars();
+            for column in 0..width {
+                map.tiles.push(match chars.next().unwrap_or(' ') {
+                    '#' => Tile::Wall,
+                    '.' | ' ' => Tile::Floor,
+                    '@' if map.start.is_none() => {
+                        map.start = Some((column as u32, row as u32));
+                        Tile::Floor
+                    }
+                    'N' => {
+                        map.npc_starts.push((column as u32, row as u32));
+                        Tile::Floor
+                    }
+                    '@' => return Err(format!("second start '@' in row {} column {}", row + 1, column + 1)),
+                    other => return Err(format!("unknown tile '{other}' in row {} column {}, use # . @ or N", row + 1, column + 1)),
+                });
+            }
+        }
+        Ok(map)
+    }
+}
+
+/*
+   -----
+   TESTS
+   -----
+ */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEVEL: &str = "#####\n#..@#\n# #\n#####\n\n";
+
+    #[test]
+    fn from_text() {
+        let map = TileMap::from_text(LEVEL).unwrap();
+
+        assert_eq!((map.width, map.height), (5, 4));
+        assert_eq!(map.tile(0, 0), Tile::Wall);
+        assert_eq!(map.tile(1, 1), Tile::Floor);
+        assert_eq!(map.tile(1, 2), Tile::Floor);
+        /* The short row is filled up with floor */
+        assert_eq!(map.tile(3, 2), Tile::Floor);
+        assert_eq!(map.tile(4, 2), Tile::Floor);
+        assert_eq!(map.start(), Some((3, 1)));
+        assert!(map.npc_starts().is_empty());
+        assert_eq!(map.tiles().filter(|&(_, _, tile)| tile == Tile::Wall).count(), 14);
+        assert_eq!(map.tiles().nth(7), Some((2, 1, Tile::Floor)));
+    }
+
+    #[test]
+    fn npc_markers() {
+        let map = TileMap::from_text("N.#\n@.N").unwrap();
+        assert_eq!(map.npc_starts(), [(0, 0), (2, 1)]);
+        assert_eq!(map.tile(0, 0), Tile::Floor);
+    }
+
+    #[test]
+    fn outside_is_wall() {
+        let map = TileMap::from_text("..\n..").unwrap();
+        assert_eq!(map.tile(-1, 0), Tile::Wall);
+        assert_eq!(map.tile(2, 0), Tile::Wall);
+        assert_eq!(map.tile(0, 2), Tile::Wall);
+    }
+
+    #[test]
+    fn from_json_matches_text() {
+        let json = TileMap::from_json("{ \"rows\": [\"#####\", \"#..@#\", \"# #\", \"#####\"] }").unwrap();
+        assert_eq!(json, TileMap::from_text(LEVEL).unwrap());
+        assert!(TileMap::from_json(r#"{ "tiles": [] }"#).is_err());
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(TileMap::from_text("\n\n"), Err("the map is empty".to_string()));
+        assert_eq!(TileMap::from_text("#.\n#x"), Err("unknown tile 'x' in row 2 column 2, use # . @ or N".to_string()));
+        assert_eq!(TileMap::from_text("@.@"), Err("second start '@' in row 1 column 3".to_string()));
+    }
+
+    #[test]
+    fn positions_and_tiles() {
+        let map = TileMap::from_text(LEVEL).unwrap();
+
+        assert_eq!(map.bounds(), Bounds::new([0.0, 50.0], [0.0, 40.0]));
+        let center = map.center(3, 1);
+        assert_eq!((center.get_x(), center.get_y()), (35.0, 25.0));
+        assert!(!map.is_blocked(&center));
+        assert!(map.is_blocked(&map.center(4, 1)));
+        assert!(map.is_blocked(Position::new().set_x(-5.0).set_y(25.0)));
+    }
+
+    #[test]
+    fn start_without_marker_is_the_bottom_left_floor() {
+        let map = TileMap::from_text("#...\n#.##\n####").unwrap();
+        assert_eq!(map.start(), Some((1, 1)));
+        assert_eq!(TileMap::from_text("##").unwrap().start(), None);
+    }
+}
@@ -0,0 +1,227 @@
+use std::{error::Error, fs, path::Path};
+
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+
+use crate::easing::Easing;
+
+/* A value at the end of a segment of a track */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Keyframe<T> {
+    pub duration_ms: u32, /* length of the segment leading to this keyframe, usually 0 for the first one */
+    pub value: T,
+    #[serde(default)]
+    pub easing: Easing /* shape of the segment leading to this keyframe */
+}
+
+/* Keyframes of one animated property, values in between are interpolated */
+pub type Track<T> = Vec<Keyframe<T>>;
+
+/* Keyframed tracks of an animation, every track runs on its own and is optional */
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Timeline {
+    pub position: Track<[f64; 2]>, /* offset from the start in movement units, x along the direction of the move and y across it */
+    pub width: Track<f64>,
+    pub height: Track<f64>,
+    pub color: Track<[u8; 3]> /* red, green and blue */
+}
+
+/* Values of all tracks at one point in time, None for tracks without keyframes */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sample {
+    pub position: Option<[f64; 2]>,
+    pub width: Option<f64>,
+    pub height: Option<f64>,
+    pub color: Option<Color>
+}
+
+/* Anything a track can interpolate between */
+pub trait Lerp: Copy {
+    fn lerp(from: Self, to: Self, phase: f64) -> Self;
+}
+
+impl Lerp for f64 {
+    fn lerp(from: f64, to: f64, phase: f64) -> f64 {
+        from + (to - from) * phase
+    }
+}
+
+impl Lerp for [f64; 2] {
+    fn lerp(from: [f64; 2], to: [f64; 2], phase: f64) -> [f64; 2] {
+        [f64::lerp(from[0], to[0], phase), f64::lerp(from[1], to[1], phase)]
+    }
+}
+
+impl Lerp for [u8; 3] {
+    fn lerp(from: [u8; 3], to: [u8; 3], phase: f64) -> [u8; 3] {
+        let channel = |i: usize| f64::lerp(from[i] as f64, to[i] as f64, phase).round().clamp(0.0, 255.0) as u8;
+        [channel(0), channel(1), channel(2)]
+    }
+}
+
+impl Timeline {
+
+    /// # Parse a timeline from JSON
+    /// Tracks that are left out stay empty
+    pub fn from_json(json: &str) -> Result<Timeline, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// # Load a timeline from a JSON file
+    pub fn load(path: &Path) -> Result<Timeline, Box<dyn Error>> {
+        let json = fs::read_to_string(path)?;
+        Ok(Timeline::from_json(&json).map_err(|err| format!("{}: {err}", path.display()))?)
+    }
+
+    /// # Length of the timeline in milliseconds
+    /// The length of the longest track
+    pub fn duration_ms(&self) -> u32 {
+        track_duration_ms(&self.position)
+            .max(track_duration_ms(&self.width))
+            .max(track_duration_ms(&self.height))
+            .max(track_duration_ms(&self.color))
+    }
+
+    /// # Is the size changing at a point in time
+    /// True inside a segment of the width or height track that goes to another value
+    pub fn size_changing(&self, time_ms: f64) -> bool {
+        segment_changes(&self.width, time_ms) || segment_changes(&self.height, time_ms)
+    }
+
+    /// # Values of all tracks at a point in time
+    /// Tracks that already ended keep their last value
+    pub fn sample(&self, time_ms: f64) -> Sample {
+        Sample {
+            position: sample_track(&self.position, time_ms),
+            width: sample_track(&self.width, time_ms),
+            height: sample_track(&self.height, time_ms),
+            color: sample_track(&self.color, time_ms).map(|[r, g, b]| Color::Rgb(r, g, b)),
+        }
+    }
+}
+
+/*--------------------
+Helper functions
+--------------------*/
+
+fn track_duration_ms<T>(track: &Track<T>) -> u32 {
+    track.iter().map(|keyframe| keyframe.duration_ms).sum()
+}
+
+fn segment_changes<T: PartialEq>(track: &Track<T>, time_ms: f64) -> bool {
+    let mut start_ms = 0.0;
+    for pair in track.windows(2) {
+        let end_ms = start_ms + pair[1].duration_ms as f64;
+        if time_ms >= start_ms && time_ms < end_ms {
+            return pair[0].value != pair[1].value;
+        }
+        start_ms = end_ms;
+    }
+    false
+}
+
+fn sample_track<T: Lerp>(track: &Track<T>, time_ms: f64) -> Option<T> {
+    let mut value = track.first()?.value;
+    let time_ms = time_ms.max(0.0); /* before the start is the first value */
+    let mut start_ms = 0.0;
+    for keyframe in track {
+        let end_ms = start_ms + keyframe.duration_ms as f64;
+        if time_ms < end_ms {
+            let phase = (time_ms - start_ms) / keyframe.duration_ms as f64;
+            return Some(T::lerp(value, keyframe.value, keyframe.easing.ease(phase)));
+        }
+        value = keyframe.value;
+        start_ms = end_ms;
+    }
+    Some(value)
+}
+
+/*
+   -----
+   TESTS
+   -----
+ */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track<T>(keyframes: &[(u32, T)]) -> Track<T> where T: Copy {
+        keyframes.iter().map(|&(duration_ms, value)| Keyframe { duration_ms, value, easing: Easing::Linear }).collect()
+    }
+
+    #[test]
+    fn sampling_a_track() {
+        let width = track(&[(0, 10.0), (200, 4.0), (100, 4.0), (100, 8.0)]);
+
+        assert_eq!(sample_track(&width, -5.0), Some(10.0));
+        assert_eq!(sample_track(&width, 0.0), Some(10.0));
+        assert_eq!(sample_track(&width, 100.0), Some(7.0));
+        assert_eq!(sample_track(&width, 250.0), Some(4.0));
+        assert_eq!(sample_track(&width, 350.0), Some(6.0));
+        assert_eq!(sample_track(&width, 400.0), Some(8.0));
+        assert_eq!(sample_track(&width, 1000.0), Some(8.0));
+        assert_eq!(sample_track(&Track::<f64>::new(), 10.0), None);
+    }
+
+    #[test]
+    fn tracks_run_independently() {
+        let timeline = Timeline {
+            position: track(&[(0, [0.0, 0.0]), (100, [10.0, -2.0])]),
+            color: track(&[(0, [0, 0, 0]), (300, [255, 30, 0])]),
+            ..Timeline::default()
+        };
+
+        assert_eq!(timeline.duration_ms(), 300);
+        assert_eq!(timeline.sample(50.0), Sample {
+            position: Some([5.0, -1.0]),
+            width: None,
+            height: None,
+            color: Some(Color::Rgb(43, 5, 0)),
+        });
+        assert_eq!(timeline.sample(300.0).position, Some([10.0, -2.0]));
+        assert_eq!(timeline.sample(300.0).color, Some(Color::Rgb(255, 30, 0)));
+    }
+
+    #[test]
+    fn size_changes() {
+        let timeline = Timeline {
+            width: track(&[(0, 10.0), (200, 3.0), (400, 3.0), (200, 10.0)]),
+            ..Timeline::default()
+        };
+
+        assert!(timeline.size_changing(0.0));
+        assert!(timeline.size_changing(199.0));
+        assert!(!timeline.size_changing(200.0));
+        assert!(!timeline.size_changing(599.0));
+        assert!(timeline.size_changing(600.0));
+        assert!(!timeline.size_changing(800.0));
+        assert!(!Timeline::default().size_changing(0.0));
+    }
+
+    #[test]
+    fn easing_per_segment() {
+        let mut width = track(&[(0, 0.0), (100, 10.0), (100, 20.0)]);
+        width[1].easing = Easing::QuadIn;
+
+        assert_eq!(sample_track(&width, 50.0), Some(2.5));
+        assert_eq!(sample_track(&width, 100.0), Some(10.0));
+        /* The second segment is still linear */
+        assert_eq!(sample_track(&width, 150.0), Some(15.0));
+    }
+
+    #[test]
+    fn from_json() {
+        let timeline = Timeline::from_json(r#"{
+            "height": [{ "duration_ms": 0, "value": 2 }, { "duration_ms": 50, "value": 6.5 }]
+        }"#).unwrap();
+
+        assert_eq!(timeline.height, track(&[(0, 2.0), (50, 6.5)]));
+        assert_eq!(Timeline::from_json(r#"{
+            "width": [{ "duration_ms": 10, "value": 1, "easing": { "cubic_bezier": [0, 0, 1, 1] } }]
+        }"#).unwrap().width[0].easing, Easing::CubicBezier([0.0, 0.0, 1.0, 1.0]));
+        assert!(timeline.position.is_empty());
+        assert!(Timeline::from_json(r#"{ "width": [{ "value": 1 }] }"#).is_err());
+    }
+}
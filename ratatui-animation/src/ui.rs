@@ -4,16 +4,24 @@ use ratatui::style::{
     Color
 };
 use ratatui::widgets::canvas::{
-    Canvas, Rectangle
+    Canvas, Context, Line, Rectangle
 };
 use ratatui::widgets::{
     Block, 
     Borders,
     Clear
 };
+use ratatui::layout::{Margin, Rect};
 use ratatui::{Frame};
 
 use crate::app::{App};
+use crate::bounds::Bounds;
+use crate::character::MOVEMENT_UNIT;
+use crate::tilemap::Tile;
+
+/* Colors of the map tiles */
+const FLOOR_COLOR: Color = Color::Black;
+const WALL_COLOR: Color = Color::Gray;
 
 /*
 Widgets are constructed and then drawn onto the screen using a Frame, which is placed within a specified Rect
@@ -30,27 +38,124 @@ pub fn ui(frame: &mut Frame, app: &App) {
         .borders(Borders::ALL)
         .style(Style::default().bg(Color::DarkGray));
 
-    /* Building rectangle for the character */
-    let x = app.character.position.get_x() - (app.character.width / 2.0);
-    let y = app.character.position.get_y() - (app.character.height / 2.0); //TODO why this works? what does x,y mean?
-    let rect_player = Rectangle {
-        x: x,
-        y: y,
-        width: app.character.width,
-        height: app.character.height,
-        color: Color::Red
-    };
+    /* Building a rectangle for every character without a sprite, in the order they are drawn */
+    let rects: Vec<Rectangle> = app.entities.by_z()
+        .into_iter()
+        .filter(|(_, character)| character.sprite.is_none())
+        .map(|(_, character)| Rectangle {
+            x: character.position.get_x() - (character.width / 2.0),
+            y: character.position.get_y() - (character.height / 2.0), //TODO why this works? what does x,y mean?
+            width: character.width,
+            height: character.height,
+            color: character.color
+        })
+        .collect();
 
     let game_canvas = Canvas::default()
         .block(game_block)
         .marker(ratatui::symbols::Marker::HalfBlock)
-        .x_bounds([0.0, 100.0]) //Scaling 0 - 100%
-        .y_bounds([0.0, 100.0]) // Scaling 0 - 100%
+        .x_bounds(app.bounds.x)
+        .y_bounds(app.bounds.y)
         .paint(|ctx| {
-            ctx.draw(&rect_player);
+            /* The map first, the characters are drawn over it */
+            if let Some(map) = &app.map {
+                let unit = MOVEMENT_UNIT as f64;
+                for (column, row, tile) in map.tiles() {
+                    let color = match tile {
+                        Tile::Floor => FLOOR_COLOR,
+                        Tile::Wall => WALL_COLOR,
+                    };
+                    let y = (map.height() - 1 - row) as f64 * unit;
+                    fill(ctx, column as f64 * unit, y, unit, unit, color);
+                }
+                ctx.layer();
+            }
+            for rect in &rects {
+                ctx.draw(rect);
+            }
         })
     ;
     frame.render_widget(game_canvas, frame.area());
 
+    /* Sprites go straight into the buffer over the canvas, on the cells the character covers */
+    let inner = frame.area().inner(Margin::new(1, 1));
+    for (_, character) in app.entities.by_z() {
+        let Some(sprite) = &character.sprite else { continue };
+        let area = to_cells(
+            inner,
+            &app.bounds,
+            character.position.get_x() - character.width / 2.0,
+            character.position.get_y() - character.height / 2.0,
+            character.width,
+            character.height,
+        );
+        sprite.frame(character.pose()).render(area, frame.buffer_mut(), character.color);
+    }
+
+}
+
+/* Canvas rectangles are only outlines, a filled one is drawn as lines close enough to leave no gaps */
+fn fill(ctx: &mut Context, x: f64, y: f64, width: f64, height: f64, color: Color) {
+    const STEP: f64 = 0.5;
+    let lines = (height / STEP) as u32;
+    for line in 0..=lines {
+        let line_y = y + (line as f64 * STEP).min(height);
+        ctx.draw(&Line::new(x, line_y, x + width, line_y, color));
+    }
+}
+
+/* Cells a box of the canvas covers, clipped to the canvas */
+fn to_cells(inner: Rect, bounds: &Bounds, x: f64, y: f64, width: f64, height: f64) -> Rect {
+    let columns = inner.width as f64 / (bounds.x[1] - bounds.x[0]);
+    let rows = inner.height as f64 / (bounds.y[1] - bounds.y[0]);
+    let column = |x: f64| ((x - bounds.x[0]) * columns).round().clamp(0.0, inner.width as f64) as u16;
+    /* The canvas y grows upwards, rows grow downwards */
+    let row = |y: f64| ((bounds.y[1] - y) * rows).round().clamp(0.0, inner.height as f64) as u16;
+
+    let (left, right) = (column(x), column(x + width));
+    let (top, bottom) = (row(y + height), row(y));
+    Rect::new(inner.x + left, inner.y + top, right - left, bottom - top)
 }
 
+/*
+   -----
+   TESTS
+   -----
+ */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use ratatui::{backend::TestBackend, Terminal};
+    use crate::sprite::Sprite;
+
+    #[test]
+    fn boxes_to_cells() {
+        let inner = Rect::new(1, 1, 40, 20);
+        let bounds = Bounds::new([0.0, 100.0], [0.0, 100.0]);
+
+        assert_eq!(to_cells(inner, &bounds, 0.0, 0.0, 10.0, 10.0), Rect::new(1, 19, 4, 2));
+        assert_eq!(to_cells(inner, &bounds, 90.0, 90.0, 10.0, 10.0), Rect::new(37, 1, 4, 2));
+        /* Partly outside is clipped */
+        assert_eq!(to_cells(inner, &bounds, -5.0, 50.0, 10.0, 10.0), Rect::new(1, 9, 2, 2));
+    }
+
+    #[test]
+    fn sprite_replaces_the_rectangle() {
+        let mut app = App::new();
+        app.bounds = Bounds::new([0.0, 20.0], [0.0, 20.0]);
+        app.player_mut().sprite = Some(Rc::new(Sprite::from_json(r#"{ "idle": [{ "rows": ["@@"] }] }"#).unwrap()));
+
+        let mut terminal = Terminal::new(TestBackend::new(10, 6)).unwrap();
+        terminal.draw(|frame| ui(frame, &app)).unwrap();
+        let buffer = terminal.backend().buffer();
+
+        /* The player covers the bottom left quarter of the 8x4 canvas */
+        let row = |y: u16| (0..10).map(|x| buffer[(x, y)].symbol()).collect::<String>();
+        assert_eq!(row(2), "│        │");
+        assert_eq!(row(3), "│@@@@    │");
+        assert_eq!(row(4), "│@@@@    │");
+        assert_eq!(buffer[(1, 4)].fg, Color::Red);
+    }
+}
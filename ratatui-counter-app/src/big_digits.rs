@@ -0,0 +1,174 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Style,
+    widgets::Widget,
+};
+
+/* 3x5 pixel glyphs, '#' is a lit pixel */
+const GLYPH_WIDTH: u16 = 3;
+const GLYPH_HEIGHT: u16 = 5;
+const GLYPHS: [(char, [&str; GLYPH_HEIGHT as usize]); 11] = [
+    ('0', ["###", "# #", "# #", "# #", "###"]),
+    ('1', [" # ", "## ", " # ", " # ", "###"]),
+    ('2', ["###", "  #", "###", "#  ", "###"]),
+    ('3', ["###", "  #", " ##", "  #", "###"]),
+    ('4', ["# #", "# #", "###", "  #", "  #"]),
+    ('5', ["###", "#  ", "###", "  #", "###"]),
+    ('6', ["###", "#  ", "###", "# #", "###"]),
+    ('7', ["###", "  #", "  #", "  #", "  #"]),
+    ('8', ["###", "# #", "###", "# #", "###"]),
+    ('9', ["###", "# #", "###", "  #", "###"]),
+    ('-', ["   ", "   ", "###", "   ", "   "]),
+];
+
+/* A number drawn with large glyphs made of half block characters */
+/* Every pixel is `scale` columns wide and `scale` half rows high, so it looks about square */
+#[derive(Debug)]
+pub struct BigNumber {
+    text: String,
+    style: Style
+}
+
+impl BigNumber {
+    pub fn new(value: i64) -> BigNumber {
+        BigNumber { text: value.to_string(), style: Style::default() }
+    }
+
+    pub fn style(mut self, style: Style) -> BigNumber {
+        self.style = style;
+        self
+    }
+
+    /// # Size in cells at a scale
+    /// Glyphs are separated by one pixel of space
+    pub fn size(&self, scale: u16) -> (u16, u16) {
+        let glyphs = self.text.chars().count() as u16;
+        let width = (glyphs * (GLYPH_WIDTH + 1)).saturating_sub(1).saturating_mul(scale);
+        let height = GLYPH_HEIGHT.saturating_mul(scale).div_ceil(2);
+        (width, height)
+    }
+
+    /// # Largest scale that fits
+    /// None if even scale 1 does not fit, the caller should fall back to plain text then
+    pub fn scale_to_fit(&self, width: u16, height: u16) -> Option<u16> {
+        (1..=u16::MAX)
+            .take_while(|&scale| {
+                let (needed_width, needed_height) = self.size(scale);
+                needed_width <= width && needed_height <= height
+            })
+            .last()
+    }
+
+    /// # Rows of the number at a scale
+    /// Each row is a string of `█`, `▀`, `▄` and spaces
+    pub fn lines(&self, scale: u16) -> Vec<String> {
+        let (width, height) = self.size(scale);
+        (0..height)
+            .map(|row| {
+                (0..width)
+                    .map(|column| match (self.pixel(column, row * 2, scale), self.pixel(column, row * 2 + 1, scale)) {
+                        (true, true) => '█',
+                        (true, false) => '▀',
+                        (false, true) => '▄',
+                        (false, false) => ' ',
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /* Is the pixel at a column and half row lit */
+    fn pixel(&self, column: u16, half_row: u16, scale: u16) -> bool {
+        let (x, y) = (column / scale, half_row / scale);
+        if y >= GLYPH_HEIGHT || x % (GLYPH_WIDTH + 1) == GLYPH_WIDTH {
+            return false; /* below the glyphs or in the space between them */
+        }
+        let Some(c) = self.text.chars().nth((x / (GLYPH_WIDTH + 1)) as usize) else {
+            return false;
+        };
+        GLYPHS
+            .iter()
+            .find(|(glyph, _)| *glyph == c)
+            .is_some_and(|(_, rows)| rows[y as usize].as_bytes()[(x % (GLYPH_WIDTH + 1)) as usize] == b'#')
+    }
+}
+
+/* Draws the number as large as it fits, centered, nothing at all if it does not fit */
+impl Widget for &BigNumber {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let Some(scale) = self.scale_to_fit(area.width, area.height) else { return };
+        let (width, height) = self.size(scale);
+        let x = area.x + (area.width - width) / 2;
+        let y = area.y + (area.height - height) / 2;
+        for (row, line) in self.lines(scale).iter().enumerate() {
+            buf.set_string(x, y + row as u16, line, self.style);
+        }
+    }
+}
+
+/*
+   -----
+   TESTS
+   -----
+ */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines_at_scale_one() {
+        assert_eq!(BigNumber::new(-42).lines(1), [
+            "    █ █ ▀▀█",
+            "▀▀▀ ▀▀█ █▀▀",
+            "      ▀ ▀▀▀",
+        ]);
+    }
+
+    #[test]
+    fn lines_at_scale_two() {
+        assert_eq!(BigNumber::new(7).lines(2), [
+            "██████",
+            "    ██",
+            "    ██",
+            "    ██",
+            "    ██",
+        ]);
+    }
+
+    #[test]
+    fn size_and_fit() {
+        let number = BigNumber::new(255);
+        assert_eq!(number.size(1), (11, 3));
+        assert_eq!(number.size(3), (33, 8));
+
+        assert_eq!(number.scale_to_fit(11, 3), Some(1));
+        assert_eq!(number.scale_to_fit(40, 8), Some(3));
+        assert_eq!(number.scale_to_fit(40, 100), Some(3));
+        assert_eq!(number.scale_to_fit(10, 3), None);
+        assert_eq!(number.scale_to_fit(11, 2), None);
+    }
+
+    #[test]
+    fn render_centered() {
+        let mut buf = Buffer::empty(Rect::new(0, 0, 9, 5));
+        (&BigNumber::new(10)).render(buf.area, &mut buf);
+
+        assert_eq!(buf, Buffer::with_lines([
+            "         ",
+            " ▄█  █▀█ ",
+            "  █  █ █ ",
+            " ▀▀▀ ▀▀▀ ",
+            "         ",
+        ]));
+    }
+
+    #[test]
+    fn too_small_renders_nothing() {
+        let mut buf = Buffer::empty(Rect::new(0, 0, 6, 5));
+        (&BigNumber::new(10)).render(buf.area, &mut buf);
+
+        assert_eq!(buf, Buffer::empty(Rect::new(0, 0, 6, 5)));
+    }
+}
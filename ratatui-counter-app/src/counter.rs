@@ -0,0 +1,302 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::history::Change;
+use crate::timer;
+
+/* What happens when a change would leave the [min, max] range */
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum OverflowMode {
+    #[default]
+    Saturate, /* stop at the bound */
+    Wrap, /* continue from the other bound */
+    Reject /* keep the value and report why */
+}
+
+impl OverflowMode {
+    pub fn parse(text: &str) -> Result<OverflowMode, String> {
+        match text.to_ascii_lowercase().as_str() {
+            "saturate" => Ok(OverflowMode::Saturate),
+            "wrap" => Ok(OverflowMode::Wrap),
+            "reject" => Ok(OverflowMode::Reject),
+            _ => Err(format!("unknown mode '{text}', use saturate, wrap or reject")),
+        }
+    }
+}
+
+impl fmt::Display for OverflowMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            OverflowMode::Saturate => "saturate",
+            OverflowMode::Wrap => "wrap",
+            OverflowMode::Reject => "reject",
+        };
+        f.write_str(name)
+    }
+}
+
+/* A named value, the app keeps a list of them */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Counter {
+    pub name: String,
+    pub value: i64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub history: Vec<Change>, /* every increment and decrement, oldest first */
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub goal: Option<i64>, /* value the user is working towards */
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reached_at: Option<u64> /* Unix time the goal was reached, None while it is not */
+}
+
+impl Counter {
+    pub fn new(name: &str, value: i64) -> Counter {
+        Counter { name: name.to_string(), value, history: Vec::new(), goal: None, reached_at: None }
+    }
+
+    /// # Is the goal reached
+    /// Counting up from `start` the value has to be at or above the goal, counting down at or
+    /// below it. False without a goal
+    pub fn goal_reached(&self, start: i64) -> bool {
+        match self.goal {
+            Some(goal) if goal >= start => self.value >= goal,
+            Some(goal) => self.value <= goal,
+            None => false,
+        }
+    }
+
+    /// # Way from `start` to the goal done so far
+    /// Between 0.0 and 1.0, None without a goal
+    pub fn goal_progress(&self, start: i64) -> Option<f64> {
+        let goal = self.goal?;
+        if goal == start {
+            return Some(1.0);
+        }
+        /* f64 of the i128 differences, so extreme values can not overflow */
+        let done = (self.value as i128 - start as i128) as f64;
+        let total = (goal as i128 - start as i128) as f64;
+        Some((done / total).clamp(0.0, 1.0))
+    }
+}
+
+/* Limits, step and automatic reset of the counters */
+#[derive(Debug, Clone, PartialEq)]
+pub struct CounterConfig {
+    pub min: i64,
+    pub max: i64,
+    pub step: i64, /* amount of one Left / Right press, always positive */
+    pub mode: OverflowMode,
+    pub reset_after: Option<u64>, /* seconds without a change before a counter goes back to its initial value */
+    pub reset_at: Option<u64> /* local time of day every counter goes back to its initial value, seconds after midnight */
+}
+
+/* Defaults match the old u8 counter */
+impl Default for CounterConfig {
+    fn default() -> Self {
+        CounterConfig {
+            min: 0,
+            max: u8::MAX as i64,
+            step: 1,
+            mode: OverflowMode::Saturate,
+            reset_after: None,
+            reset_at: None,
+        }
+    }
+}
+
+impl CounterConfig {
+
+    /// # Build the config from command line arguments
+    /// Understands `--min N`, `--max N`, `--step N`, `--mode saturate|wrap|reject`,
+    /// `--reset-after 10m` and `--reset-at HH:MM`, everything not given keeps its default
+    pub fn from_args(args: &[String]) -> Result<CounterConfig, String> {
+        let mut config = CounterConfig::default();
+        let mut args = args.iter();
+        while let Some(flag) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("missing value after {flag}"));
+            match flag.as_str() {
+                "--min" => config.min = parse_number(flag, value()?)?,
+                "--max" => config.max = parse_number(flag, value()?)?,
+                "--step" => config.step = parse_number(flag, value()?)?,
+                "--mode" => config.mode = OverflowMode::parse(value()?)?,
+                "--reset-after" => config.reset_after = Some(timer::parse_duration(value()?)?),
+                "--reset-at" => config.reset_at = Some(timer::parse_clock_time(value()?)?),
+                _ => return Err(format!("unknown argument '{flag}'")),
+            }
+        }
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.min > self.max {
+            return Err(format!("min ({}) is greater than max ({})", self.min, self.max));
+        }
+        if self.step <= 0 {
+            return Err(format!("step has to be positive, got {}", self.step));
+        }
+        Ok(())
+    }
+
+    /// # Starting value
+    /// Zero if it is inside the bounds, otherwise the closest bound
+    pub fn initial_value(&self) -> i64 {
+        0.clamp(self.min, self.max)
+    }
+
+    /// # Add a signed amount to a value
+    /// The result is kept inside [min, max] according to the mode, Reject returns the
+    /// message to show instead of a new value. Never overflows, whatever the bounds are
+    pub fn apply(&self, value: i64, delta: i64) -> Result<i64, String> {
+        /* i128 can hold any sum of two i64, so nothing can overflow below */
+        let target = value as i128 + delta as i128;
+        let (min, max) = (self.min as i128, self.max as i128);
+        if (min..=max).contains(&target) {
+            return Ok(target as i64);
+        }
+        match self.mode {
+            OverflowMode::Saturate => Ok(target.clamp(min, max) as i64),
+            OverflowMode::Wrap => {
+                let span = max - min + 1;
+                Ok((min + (target - min).rem_euclid(span)) as i64)
+            }
+            OverflowMode::Reject if target > max => Err(format!("Can not go above {}", self.max)),
+            OverflowMode::Reject => Err(format!("Can not go below {}", self.min)),
+        }
+    }
+}
+
+fn parse_number(flag: &str, value: &str) -> Result<i64, String> {
+    value.parse().map_err(|_| format!("{flag} expects a whole number, got '{value}'"))
+}
+
+/*
+   -----
+   TESTS
+   -----
+ */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(min: i64, max: i64, mode: OverflowMode) -> CounterConfig {
+        CounterConfig { min, max, step: 1, mode, ..Default::default() }
+    }
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn inside_bounds_is_plain_addition() {
+        for mode in [OverflowMode::Saturate, OverflowMode::Wrap, OverflowMode::Reject] {
+            let config = config(-5, 5, mode);
+            assert_eq!(config.apply(0, 1), Ok(1));
+            assert_eq!(config.apply(0, -5), Ok(-5));
+            assert_eq!(config.apply(4, 1), Ok(5));
+        }
+    }
+
+    #[test]
+    fn saturate_stops_at_bounds() {
+        let config = config(0, 255, OverflowMode::Saturate);
+        assert_eq!(config.apply(0, -1), Ok(0));
+        assert_eq!(config.apply(255, 1), Ok(255));
+        assert_eq!(config.apply(250, 10), Ok(255));
+        assert_eq!(config.apply(3, -10), Ok(0));
+    }
+
+    #[test]
+    fn wrap_continues_from_the_other_bound() {
+        let byte = config(0, 255, OverflowMode::Wrap);
+        assert_eq!(byte.apply(0, -1), Ok(255));
+        assert_eq!(byte.apply(255, 1), Ok(0));
+        assert_eq!(byte.apply(250, 10), Ok(4));
+        assert_eq!(byte.apply(3, -10), Ok(249));
+
+        let small = config(-2, 2, OverflowMode::Wrap);
+        assert_eq!(small.apply(2, 1), Ok(-2));
+        assert_eq!(small.apply(-2, -1), Ok(2));
+        assert_eq!(small.apply(0, 12), Ok(2));
+    }
+
+    #[test]
+    fn reject_keeps_the_value() {
+        let config = config(0, 10, OverflowMode::Reject);
+        assert_eq!(config.apply(0, -1), Err("Can not go below 0".to_string()));
+        assert_eq!(config.apply(10, 1), Err("Can not go above 10".to_string()));
+        assert_eq!(config.apply(8, 2), Ok(10));
+    }
+
+    #[test]
+    fn extreme_bounds_do_not_overflow() {
+        let saturate = config(i64::MIN, i64::MAX, OverflowMode::Saturate);
+        assert_eq!(saturate.apply(i64::MAX, 1), Ok(i64::MAX));
+        assert_eq!(saturate.apply(i64::MIN, -1), Ok(i64::MIN));
+
+        let wrap = config(i64::MIN, i64::MAX, OverflowMode::Wrap);
+        assert_eq!(wrap.apply(i64::MAX, 1), Ok(i64::MIN));
+        assert_eq!(wrap.apply(i64::MIN, -1), Ok(i64::MAX));
+
+        let single = config(7, 7, OverflowMode::Wrap);
+        assert_eq!(single.apply(7, 1), Ok(7));
+    }
+
+    #[test]
+    fn initial_value_is_inside_bounds() {
+        assert_eq!(CounterConfig::default().initial_value(), 0);
+        assert_eq!(config(5, 10, OverflowMode::Saturate).initial_value(), 5);
+        assert_eq!(config(-10, -5, OverflowMode::Saturate).initial_value(), -5);
+    }
+
+    #[test]
+    fn goals() {
+        let mut counter = Counter::new("reviews", 0);
+        assert!(!counter.goal_reached(0));
+        assert_eq!(counter.goal_progress(0), None);
+
+        counter.goal = Some(50);
+        counter.value = 10;
+        assert!(!counter.goal_reached(0));
+        assert_eq!(counter.goal_progress(0), Some(0.2));
+        counter.value = 60;
+        assert!(counter.goal_reached(0));
+        assert_eq!(counter.goal_progress(0), Some(1.0));
+
+        /* Counting down from 100 to 40 */
+        counter.goal = Some(40);
+        counter.value = 70;
+        assert!(!counter.goal_reached(100));
+        assert_eq!(counter.goal_progress(100), Some(0.5));
+        counter.value = 40;
+        assert!(counter.goal_reached(100));
+
+        counter.goal = Some(i64::MAX);
+        counter.value = i64::MIN;
+        assert_eq!(counter.goal_progress(i64::MIN), Some(0.0));
+        counter.goal = Some(0);
+        assert_eq!(counter.goal_progress(0), Some(1.0));
+    }
+
+    #[test]
+    fn from_args() {
+        assert_eq!(CounterConfig::from_args(&[]), Ok(CounterConfig::default()));
+        assert_eq!(
+            CounterConfig::from_args(&args(&["--min", "-10", "--max", "10", "--step", "5", "--mode", "wrap"])),
+            Ok(CounterConfig { min: -10, max: 10, step: 5, mode: OverflowMode::Wrap, ..Default::default() })
+        );
+        assert_eq!(
+            CounterConfig::from_args(&args(&["--reset-after", "10m", "--reset-at", "06:30"])),
+            Ok(CounterConfig { reset_after: Some(600), reset_at: Some(23_400), ..Default::default() })
+        );
+        assert!(CounterConfig::from_args(&args(&["--reset-after", "soon"])).is_err());
+        assert!(CounterConfig::from_args(&args(&["--reset-at", "25:00"])).is_err());
+        assert!(CounterConfig::from_args(&args(&["--min", "5", "--max", "1"])).is_err());
+        assert!(CounterConfig::from_args(&args(&["--step", "0"])).is_err());
+        assert!(CounterConfig::from_args(&args(&["--step", "x"])).is_err());
+        assert!(CounterConfig::from_args(&args(&["--mode", "bounce"])).is_err());
+        assert!(CounterConfig::from_args(&args(&["--max"])).is_err());
+        assert!(CounterConfig::from_args(&args(&["--verbose"])).is_err());
+    }
+}
@@ -0,0 +1,146 @@
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+/* Oldest changes are dropped past this, so the state file can not grow forever */
+pub const MAX_CHANGES: usize = 10_000;
+
+/* One increment or decrement of a counter */
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Change {
+    pub at: u64, /* seconds since the Unix epoch */
+    pub value: i64 /* value of the counter after the change */
+}
+
+/* Size of one bar of the history chart */
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Bucket {
+    #[default]
+    Minute,
+    Hour,
+    Day
+}
+
+impl Bucket {
+    pub fn seconds(self) -> u64 {
+        match self {
+            Bucket::Minute => 60,
+            Bucket::Hour => 60 * 60,
+            Bucket::Day => 24 * 60 * 60,
+        }
+    }
+
+    /* Minute -> Hour -> Day -> Minute */
+    pub fn next(self) -> Bucket {
+        match self {
+            Bucket::Minute => Bucket::Hour,
+            Bucket::Hour => Bucket::Day,
+            Bucket::Day => Bucket::Minute,
+        }
+    }
+}
+
+impl fmt::Display for Bucket {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Bucket::Minute => "minute",
+            Bucket::Hour => "hour",
+            Bucket::Day => "day",
+        };
+        f.write_str(name)
+    }
+}
+
+/// # Current time for the history
+/// Seconds since the Unix epoch, a clock before 1970 counts as 0
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs())
+}
+
+/// # Remember a change
+/// Keeps at most MAX_CHANGES, dropping the oldest ones
+pub fn record(changes: &mut Vec<Change>, change: Change) {
+    changes.push(change);
+    if changes.len() > MAX_CHANGES {
+        changes.drain(..changes.len() - MAX_CHANGES);
+    }
+}
+
+/// # Number of changes per bucket
+/// Returns `count` buckets, oldest first, the last one is the bucket `now` falls into.
+/// Buckets are aligned to the epoch, so a minute bucket starts at a full minute
+pub fn counts(changes: &[Change], bucket: Bucket, now: u64, count: usize) -> Vec<u64> {
+    let mut counts = vec![0; count];
+    let current = now / bucket.seconds();
+    for change in changes {
+        let age = current.checked_sub(change.at / bucket.seconds());
+        if let Some(age) = age.filter(|&age| age < count as u64) {
+            counts[count - 1 - age as usize] += 1;
+        }
+    }
+    counts
+}
+
+/*
+   -----
+   TESTS
+   -----
+ */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(times: &[u64]) -> Vec<Change> {
+        times.iter().map(|&at| Change { at, value: 0 }).collect()
+    }
+
+    #[test]
+    fn counts_per_minute() {
+        let changes = at(&[0, 59, 60, 150, 179, 180]);
+
+        assert_eq!(counts(&changes, Bucket::Minute, 185, 4), [2, 1, 2, 1]);
+        /* Only the last two minutes fit */
+        assert_eq!(counts(&changes, Bucket::Minute, 185, 2), [2, 1]);
+        /* Empty minutes after the last change */
+        assert_eq!(counts(&changes, Bucket::Minute, 300, 3), [1, 0, 0]);
+    }
+
+    #[test]
+    fn counts_per_hour_and_day() {
+        let changes = at(&[10, 3_000, 3_700, 86_500]);
+
+        assert_eq!(counts(&changes, Bucket::Hour, 86_500, 25), {
+            let mut expected = vec![0; 25];
+            expected[0] = 2;
+            expected[1] = 1;
+            expected[24] = 1;
+            expected
+        });
+        assert_eq!(counts(&changes, Bucket::Day, 86_500, 3), [0, 3, 1]);
+    }
+
+    #[test]
+    fn changes_from_the_future_are_ignored() {
+        assert_eq!(counts(&at(&[500]), Bucket::Minute, 100, 3), [0, 0, 0]);
+        assert_eq!(counts(&at(&[500]), Bucket::Minute, 100, 0), Vec::<u64>::new());
+    }
+
+    #[test]
+    fn record_drops_the_oldest() {
+        let mut changes = at(&[1; MAX_CHANGES]);
+        record(&mut changes, Change { at: 2, value: 1 });
+
+        assert_eq!(changes.len(), MAX_CHANGES);
+        assert_eq!(changes.last(), Some(&Change { at: 2, value: 1 }));
+    }
+
+    #[test]
+    fn buckets_cycle() {
+        assert_eq!(Bucket::Minute.next().next().next(), Bucket::Minute);
+        assert_eq!(Bucket::Hour.to_string(), "hour");
+    }
+}
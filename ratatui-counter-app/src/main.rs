@@ -1,23 +1,77 @@
-use std::io;
+use std::{
+    env, io, mem, thread,
+    io::Write,
+    path::PathBuf,
+    sync::mpsc::{self, Receiver, RecvTimeoutError, Sender},
+    time::Duration,
+};
 
-use crossterm::event:: {self, Event, KeyCode, KeyEvent, KeyEventKind};
+use crossterm::event:: {self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
 
 use ratatui::{
     buffer::Buffer,
-    layout::Rect,
-    style::Stylize,
-    symbols::border,
+    layout::{Constraint, Flex, Layout, Rect},
+    style::{Style, Stylize},
+    symbols::{self, border},
     text::{Line, Text},
-    widgets::{canvas::{Canvas, Shape}, Block, Paragraph, Widget},
+    widgets::{Block, Borders, Clear, LineGauge, List, ListItem, ListState, Paragraph, Sparkline, StatefulWidget, Widget},
     DefaultTerminal, Frame
 };
 
+mod big_digits;
+mod counter;
+mod history;
+#[cfg(unix)]
+mod socket;
+mod storage;
+mod timer;
+use crate::big_digits::BigNumber;
+use crate::counter::{Counter, CounterConfig};
+use crate::history::{Bucket, Change};
+#[cfg(unix)]
+use crate::socket::{Command, ControlSocket, Request};
+use crate::storage::{SavedState, StateFile};
+
+/* Longest wait for an event, the idle timer and auto-reset move at least this often */
+const TICK_RATE: Duration = Duration::from_millis(250);
+
+/* Oldest edits can not be undone past this, so a long running app does not grow forever */
+const MAX_UNDO: usize = 1_000;
+
+/* How long the border flashes after a goal is reached */
+const FLASH_SECONDS: u64 = 2;
+
 fn main() -> io::Result<()> {
+    /* Bounds, step and overflow mode come from the command line, before touching the terminal */
+    /* --state <file> replaces the default state file in the user's data directory */
+    /* --socket <path> lets other programs change the counters, see socket.rs for the commands */
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let invalid_input = |err| io::Error::new(io::ErrorKind::InvalidInput, err);
+    let state_path = take_flag(&mut args, "--state").map_err(invalid_input)?
+        .map(PathBuf::from)
+        .unwrap_or_else(StateFile::default_path);
+    let socket_path = take_flag(&mut args, "--socket").map_err(invalid_input)?.map(PathBuf::from);
+    let config = CounterConfig::from_args(&args).map_err(invalid_input)?;
+
+    /* Counters of the last run, a missing or broken file only shows a warning */
+    let mut app = App::new(config);
+    app.load_state(StateFile::new(state_path));
+
+    /* Keys and socket commands arrive on the same channel, so either wakes the loop at once */
+    let (sender, events) = mpsc::channel();
+    #[cfg(unix)]
+    let _socket = socket_path.map(|path| ControlSocket::bind(path, sender.clone())).transpose()?;
+    #[cfg(not(unix))]
+    if socket_path.is_some() {
+        return Err(invalid_input("--socket needs Unix domain sockets".to_string()));
+    }
+    read_input(sender);
+
     /* Initializing the default terminal */
     let mut terminal = ratatui::init();
 
     /* Usually this would be in a loop, running the applicaiton */
-    let app_result = App::default().run(&mut terminal);
+    let app_result = app.run(&mut terminal, &events);
     
     /* When GUI ended, restore the terminal */
     ratatui::restore();
@@ -26,62 +80,561 @@ fn main() -> io::Result<()> {
     app_result
 }
 
-#[derive(Debug, Default)]
+/* Removes `flag <value>` from the arguments and returns the value */
+fn take_flag(args: &mut Vec<String>, flag: &str) -> Result<Option<String>, String> {
+    let Some(index) = args.iter().position(|arg| arg == flag) else {
+        return Ok(None);
+    };
+    if index + 1 >= args.len() {
+        return Err(format!("missing value after {flag}"));
+    }
+    let value = args.remove(index + 1);
+    args.remove(index);
+    Ok(Some(value))
+}
+
+/* Everything the main loop waits for */
+#[derive(Debug)]
+enum AppEvent {
+    Input(Event), /* from the terminal */
+    InputFailed(io::Error), /* the terminal can not be read anymore */
+    #[cfg(unix)]
+    Command(Request) /* from the control socket */
+}
+
+#[cfg(unix)]
+impl From<Request> for AppEvent {
+    fn from(request: Request) -> AppEvent {
+        AppEvent::Command(request)
+    }
+}
+
+/* Reads terminal events on their own thread, until reading fails or the app is gone */
+fn read_input(events: Sender<AppEvent>) {
+    thread::spawn(move || loop {
+        let event = match event::read() {
+            Ok(event) => AppEvent::Input(event),
+            Err(err) => {
+                let _ = events.send(AppEvent::InputFailed(err));
+                return;
+            }
+        };
+        if events.send(event).is_err() {
+            return;
+        }
+    });
+}
+
+#[derive(Debug)]
 pub struct App {
-    counter: u8,
+    counters: Vec<Counter>, /* Never empty, the last counter can not be deleted */
+    selected: usize, /* Index of the counter Left / Right are changing */
+    config: CounterConfig,
+    message: Option<String>, /* Why the last change was rejected, shown under the value */
+    popup: Option<Popup>, /* Input window on top of the counters, gets every key while open */
+    state_file: Option<StateFile>, /* Where the counters are saved, None keeps them in memory only */
+    dirty: bool, /* Counters or selection changed since the last save */
+    undo: Vec<Edit>, /* Value changes, the last one is undone first, at most MAX_UNDO */
+    redo: Vec<Edit>, /* Undone changes, emptied by any new change */
+    bucket: Bucket, /* Time span of one bar of the history chart */
+    clock: fn() -> u64, /* Seconds since the Unix epoch, replaced in tests */
+    last_tick: Option<u64>, /* Clock of the previous tick, to notice passing the reset time */
+    bell: bool, /* Ring the terminal bell after the next draw */
+    flash_until: Option<u64>, /* Clock until which the border is highlighted */
     exit: bool
 }
+
+/* The small input window used to manage counters */
+#[derive(Debug, Clone, PartialEq)]
+enum Popup {
+    Add(String), /* name of the new counter being typed */
+    Rename(String), /* new name of the selected counter being typed */
+    Delete, /* asking for confirmation */
+    Entry(String), /* new value of the selected counter being typed */
+    Goal(String) /* goal of the selected counter being typed, empty removes it */
+}
+
+/* One value change of one counter, as kept for undo / redo */
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Edit {
+    counter: usize, /* index into App.counters */
+    before: i64,
+    after: i64
+}
+
+impl Default for App {
+    fn default() -> Self {
+        App::new(CounterConfig::default())
+    }
+}
+
 impl App {
 
+    pub fn new(config: CounterConfig) -> App {
+        App {
+            counters: vec![Counter::new("counter", config.initial_value())],
+            selected: 0,
+            config,
+            message: None,
+            popup: None,
+            state_file: None,
+            dirty: false,
+            undo: Vec::new(),
+            redo: Vec::new(),
+            bucket: Bucket::default(),
+            clock: history::unix_now,
+            last_tick: None,
+            bell: false,
+            flash_until: None,
+            exit: false
+        }
+    }
+
     /* Runs the application's main loop until the user quits */
-    pub fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+    /* The state is saved after every event that changed it, and once more on exit */
+    /* A failed save on exit ends the app with the error, as there is no screen left to show it */
+    /* Waiting for an event takes at most one tick, so the screen is redrawn even without input */
+    fn run(&mut self, terminal: &mut DefaultTerminal, events: &Receiver<AppEvent>) -> io::Result<()> {
         while !self.exit {
             terminal.draw( |frame| self.draw(frame))?;
-            self.handle_events()?;
+            if mem::take(&mut self.bell) {
+                let mut stdout = io::stdout();
+                stdout.write_all(b"\x07")?;
+                stdout.flush()?;
+            }
+            match events.recv_timeout(TICK_RATE) {
+                Ok(event) => self.handle_app_event(event)?,
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+            self.tick();
+        }
+        self.save_state()
+    }
+
+    fn handle_app_event(&mut self, event: AppEvent) -> io::Result<()> {
+        match event {
+            AppEvent::Input(event) => self.handle_event(event),
+            AppEvent::InputFailed(err) => return Err(err),
+            #[cfg(unix)]
+            AppEvent::Command(request) => {
+                let reply = self.handle_command(request.command);
+                /* The client may have hung up already, nothing to do about that */
+                let _ = request.reply.send(reply);
+            }
         }
         Ok(())
     }
+
+    /* Restores the counters from the file and remembers it for saving */
+    /* Any problem with the file is shown as a warning, the defaults stay in place */
+    pub fn load_state(&mut self, state_file: StateFile) {
+        match state_file.load() {
+            Ok(state) => self.restore(state),
+            Err(warning) => self.message = Some(warning),
+        }
+        self.state_file = Some(state_file);
+    }
+
+    fn saved_state(&self) -> SavedState {
+        SavedState {
+            counters: self.counters.clone(),
+            selected: self.selected,
+        }
+    }
+
+    /* Values are brought inside the current bounds, they may have changed since the save */
+    fn restore(&mut self, state: SavedState) {
+        self.counters = state.counters;
+        for counter in &mut self.counters {
+            counter.value = counter.value.clamp(self.config.min, self.config.max);
+        }
+        self.selected = state.selected.min(self.counters.len() - 1);
+        self.dirty = true;
+    }
+
+    /* The error names the file, it is shown to the user as it is */
+    fn save_state(&self) -> io::Result<()> {
+        let Some(state_file) = &self.state_file else { return Ok(()) };
+        state_file.save(&self.saved_state()).map_err(|err| {
+            io::Error::new(err.kind(), format!("Could not save {}: {err}", state_file.path().display()))
+        })
+    }
+
+    /* Saves if anything changed since the last save, a failed save is shown as the message */
+    fn save_if_changed(&mut self) {
+        if mem::take(&mut self.dirty)
+            && let Err(err) = self.save_state()
+        {
+            self.message = Some(err.to_string());
+        }
+    }
     
     fn draw(&self, frame: &mut Frame) {
         frame.render_widget(self, frame.area() ); 
         /* Rendering self works, because we implemented *Widget* for self */
     }
 
-    // This function blocks!!! Unit there is an event from crossterm.
-    // Better approach to use event::poll
-    fn handle_events(&mut self) -> io::Result<()> {
-        match event::read()? {
+    /* Time based work, runs after every event and at least once per TICK_RATE */
+    /* Counters idle for reset_after, or all of them when the clock passes reset_at, are reset */
+    fn tick(&mut self) {
+        let now = (self.clock)();
+        if let Some(idle) = self.config.reset_after {
+            for index in 0..self.counters.len() {
+                if self.idle_seconds(index, now).is_some_and(|seconds| seconds >= idle) {
+                    self.reset_counter(index, &format!("after {} without changes", timer::format_duration(idle)));
+                }
+            }
+        }
+        if let (Some(at), Some(last_tick)) = (self.config.reset_at, self.last_tick)
+            && timer::passed_time_of_day(at, timer::local_offset(now), last_tick, now)
+        {
+            for index in 0..self.counters.len() {
+                self.reset_counter(index, &format!("at {}", clock_time(at)));
+            }
+        }
+        self.last_tick = Some(now);
+        self.save_if_changed();
+    }
+
+    /* Seconds since the last change of a counter, None if it never changed */
+    fn idle_seconds(&self, index: usize, now: u64) -> Option<u64> {
+        let last_change = self.counters[index].history.last()?;
+        Some(now.saturating_sub(last_change.at))
+    }
+
+    /* Back to the initial value, like any change it can be undone */
+    fn reset_counter(&mut self, index: usize, reason: &str) {
+        let initial = self.config.initial_value();
+        if self.counters[index].value != initial {
+            self.edit(index, initial);
+            self.message = Some(format!("'{}' was reset {reason}", self.counters[index].name));
+        }
+    }
+
+    /* Applies one event, and saves the state if the event changed it */
+    fn handle_event(&mut self, event: Event) {
+        match event {
             Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
                 self.handle_key_event(key_event)
             }
             _ => {}
         };
-        Ok(())
+        self.save_if_changed();
+    }
+
+    /* Applies a command of the control socket, the reply is `ok <value>` or `error <message>` */
+    #[cfg(unix)]
+    fn handle_command(&mut self, command: Command) -> String {
+        let result = self.apply_command(command);
+        self.save_if_changed();
+        match result {
+            Ok(value) => format!("ok {value}"),
+            Err(err) => format!("error {err}"),
+        }
+    }
+
+    /* Commands work like the keys, on the named counter or the selected one */
+    #[cfg(unix)]
+    fn apply_command(&mut self, command: Command) -> Result<i64, String> {
+        let (Command::Increment(name) | Command::Decrement(name) | Command::Set(_, name) | Command::Get(name)) = &command;
+        let index = match name {
+            Some(name) => self.counters.iter()
+                .position(|counter| &counter.name == name)
+                .ok_or_else(|| format!("There is no counter named '{name}'"))?,
+            None => self.selected,
+        };
+        match command {
+            Command::Increment(_) => self.change_value(index, self.config.step)?,
+            Command::Decrement(_) => self.change_value(index, -self.config.step)?,
+            Command::Set(value, _) => {
+                self.check_value(value)?;
+                self.edit(index, value);
+            }
+            Command::Get(_) => {}
+        }
+        Ok(self.counters[index].value)
     }
 
     fn handle_key_event(&mut self, key_event: KeyEvent) {
+        if self.popup.is_some() {
+            self.handle_popup_key_event(key_event);
+            return;
+        }
+        let steps = step_multiplier(key_event.modifiers);
         match key_event.code {
             KeyCode::Char('q') => self.exit(),
-            KeyCode::Left => self.decrement_counter(),
-            KeyCode::Right => self.increment_counter(),
+            KeyCode::Left => self.decrement_counter(steps),
+            KeyCode::Right => self.increment_counter(steps),
+            KeyCode::Up => self.select_previous(),
+            KeyCode::Down => self.select_next(),
+            KeyCode::Char('a') => self.popup = Some(Popup::Add(String::new())),
+            KeyCode::Char('r') if key_event.modifiers.contains(KeyModifiers::CONTROL) => self.redo(),
+            KeyCode::Char('r') => self.popup = Some(Popup::Rename(self.counter().name.clone())),
+            KeyCode::Char('d') => self.popup = Some(Popup::Delete),
+            KeyCode::Char('b') => self.bucket = self.bucket.next(),
+            KeyCode::Char('g') => {
+                let goal = self.counter().goal.map(|goal| goal.to_string()).unwrap_or_default();
+                self.popup = Some(Popup::Goal(goal));
+            }
+            KeyCode::Char('u') => self.undo(),
+            KeyCode::Char(digit @ '0'..='9') => self.popup = Some(Popup::Entry(digit.to_string())),
             _ => {}
         }
     }
 
+    /* Keys while the popup is open, errors keep it open with the message shown */
+    fn handle_popup_key_event(&mut self, key_event: KeyEvent) {
+        let Some(popup) = &mut self.popup else { return };
+        let result = match (popup, key_event.code) {
+            (_, KeyCode::Esc) | (Popup::Delete, KeyCode::Char('n')) => Ok(()),
+            (Popup::Delete, KeyCode::Char('y')) => self.delete_selected(),
+            (Popup::Add(name) | Popup::Rename(name), KeyCode::Char(c)) => {
+                name.push(c);
+                return;
+            }
+            /* Only digits, and a minus sign in front */
+            (Popup::Entry(input) | Popup::Goal(input), KeyCode::Char(c)) => {
+                if c.is_ascii_digit() || (c == '-' && input.is_empty()) {
+                    input.push(c);
+                }
+                return;
+            }
+            (Popup::Add(text) | Popup::Rename(text) | Popup::Entry(text) | Popup::Goal(text), KeyCode::Backspace) => {
+                text.pop();
+                return;
+            }
+            (Popup::Add(name), KeyCode::Enter) => {
+                let name = name.clone();
+                self.add_counter(&name)
+            }
+            (Popup::Rename(name), KeyCode::Enter) => {
+                let name = name.clone();
+                self.rename_selected(&name)
+            }
+            (Popup::Entry(input), KeyCode::Enter) => {
+                let input = input.clone();
+                self.enter_value(&input)
+            }
+            (Popup::Goal(input), KeyCode::Enter) => {
+                let input = input.clone();
+                self.set_goal(&input)
+            }
+            _ => return,
+        };
+        match result {
+            Ok(()) => {
+                self.popup = None;
+                self.message = None;
+            }
+            Err(message) => self.message = Some(message),
+        }
+    }
+
     fn exit(&mut self) {
         self.exit = true;
     }
 
-    fn decrement_counter(&mut self) {
-        self.counter -= 1;
+    /* The counter Left / Right are changing */
+    fn counter(&self) -> &Counter {
+        &self.counters[self.selected]
+    }
+
+    fn select_previous(&mut self) {
+        self.select(self.selected.saturating_sub(1));
+    }
+
+    fn select_next(&mut self) {
+        self.select((self.selected + 1).min(self.counters.len() - 1));
+    }
+
+    fn select(&mut self, index: usize) {
+        self.dirty |= index != self.selected;
+        self.selected = index;
+        self.message = None;
+    }
+
+    /* By `steps` times the configured step */
+    fn decrement_counter(&mut self, steps: i64) {
+        self.change_counter(-self.config.step.saturating_mul(steps));
     }
 
-    fn increment_counter(&mut self) {
-        self.counter += 1;
+    fn increment_counter(&mut self, steps: i64) {
+        self.change_counter(self.config.step.saturating_mul(steps));
     }
 
+    fn change_counter(&mut self, delta: i64) {
+        match self.change_value(self.selected, delta) {
+            Ok(()) => self.message = None,
+            Err(message) => self.message = Some(message),
+        }
+    }
 
+    /* Every change goes through the config, so the bounds and the mode are respected */
+    fn change_value(&mut self, index: usize, delta: i64) -> Result<(), String> {
+        let value = self.config.apply(self.counters[index].value, delta)?;
+        self.edit(index, value);
+        Ok(())
+    }
 
+    fn enter_value(&mut self, input: &str) -> Result<(), String> {
+        let value: i64 = input.parse().map_err(|_| format!("'{input}' is not a whole number"))?;
+        self.check_value(value)?;
+        self.edit_selected(value);
+        Ok(())
+    }
+
+    /* Set values are not wrapped or clamped, a value outside the bounds is refused */
+    fn check_value(&self, value: i64) -> Result<(), String> {
+        if !(self.config.min..=self.config.max).contains(&value) {
+            return Err(format!("Value has to be between {} and {}", self.config.min, self.config.max));
+        }
+        Ok(())
+    }
+
+    fn edit_selected(&mut self, value: i64) {
+        self.edit(self.selected, value);
+    }
+
+    /* New value for a counter, it can be undone */
+    fn edit(&mut self, index: usize, value: i64) {
+        let before = self.counters[index].value;
+        if value == before {
+            return;
+        }
+        self.undo.push(Edit { counter: index, before, after: value });
+        if self.undo.len() > MAX_UNDO {
+            self.undo.drain(..self.undo.len() - MAX_UNDO);
+        }
+        self.redo.clear();
+        self.set_value(index, value);
+    }
+
+    /* Reverts the last change and selects the counter it was made to */
+    fn undo(&mut self) {
+        let Some(edit) = self.undo.pop() else {
+            self.message = Some("Nothing to undo".to_string());
+            return;
+        };
+        self.set_value(edit.counter, edit.before);
+        self.redo.push(edit);
+        self.selected = edit.counter;
+        self.message = None;
+    }
+
+    fn redo(&mut self) {
+        let Some(edit) = self.redo.pop() else {
+            self.message = Some("Nothing to redo".to_string());
+            return;
+        };
+        self.set_value(edit.counter, edit.after);
+        self.undo.push(edit);
+        self.selected = edit.counter;
+        self.message = None;
+    }
+
+    /* Every value change, including undo and redo, ends up in the counter's history */
+    fn set_value(&mut self, index: usize, value: i64) {
+        let counter = &mut self.counters[index];
+        history::record(&mut counter.history, Change { at: (self.clock)(), value });
+        counter.value = value;
+        self.dirty = true;
+        self.check_goal(index);
+    }
+
+    /* An empty input removes the goal, a goal outside the bounds could never be reached */
+    fn set_goal(&mut self, input: &str) -> Result<(), String> {
+        let goal = match input {
+            "" => None,
+            _ => {
+                let goal: i64 = input.parse().map_err(|_| format!("'{input}' is not a whole number"))?;
+                self.check_value(goal)?;
+                Some(goal)
+            }
+        };
+        let counter = &mut self.counters[self.selected];
+        counter.goal = goal;
+        counter.reached_at = None;
+        self.dirty = true;
+        self.check_goal(self.selected);
+        Ok(())
+    }
+
+    /* Stores when a counter reaches its goal and rings the bell, falling back below forgets it */
+    fn check_goal(&mut self, index: usize) {
+        let now = (self.clock)();
+        let initial = self.config.initial_value();
+        let counter = &mut self.counters[index];
+        match (counter.goal_reached(initial), counter.reached_at) {
+            (true, None) => {
+                counter.reached_at = Some(now);
+                self.bell = true;
+                self.flash_until = Some(now + FLASH_SECONDS);
+            }
+            (false, Some(_)) => counter.reached_at = None,
+            _ => {}
+        }
+    }
+
+    /* Adds a new counter at the end of the list and selects it */
+    fn add_counter(&mut self, name: &str) -> Result<(), String> {
+        let name = self.check_name(name, None)?;
+        self.counters.push(Counter::new(&name, self.config.initial_value()));
+        self.selected = self.counters.len() - 1;
+        self.dirty = true;
+        Ok(())
+    }
+
+    fn rename_selected(&mut self, name: &str) -> Result<(), String> {
+        let name = self.check_name(name, Some(self.selected))?;
+        self.counters[self.selected].name = name;
+        self.dirty = true;
+        Ok(())
+    }
+
+    fn delete_selected(&mut self) -> Result<(), String> {
+        if self.counters.len() == 1 {
+            return Err("Can not delete the last counter".to_string());
+        }
+        self.counters.remove(self.selected);
+        self.selected = self.selected.min(self.counters.len() - 1);
+        self.dirty = true;
+        /* The edits point at counters by index, which just shifted */
+        self.undo.clear();
+        self.redo.clear();
+        Ok(())
+    }
+
+    /* Names are trimmed, can not be empty and have to be unique (except for the renamed counter itself) */
+    fn check_name(&self, name: &str, renaming: Option<usize>) -> Result<String, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Name can not be empty".to_string());
+        }
+        let taken = self.counters.iter().enumerate()
+            .any(|(i, counter)| counter.name == name && Some(i) != renaming);
+        if taken {
+            return Err(format!("There is already a counter named '{name}'"));
+        }
+        Ok(name.to_string())
+    }
+
+}
+
+/* Seconds after midnight as HH:MM */
+fn clock_time(seconds: u64) -> String {
+    format!("{:02}:{:02}", seconds / 3600, seconds % 3600 / 60)
+}
+
+/* Shift moves 10 steps at once, Ctrl 100 */
+fn step_multiplier(modifiers: KeyModifiers) -> i64 {
+    if modifiers.contains(KeyModifiers::CONTROL) {
+        100
+    } else if modifiers.contains(KeyModifiers::SHIFT) {
+        10
+    } else {
+        1
+    }
 }
 /* Widget is a high level renderable object
    Widget impelements .render() which is used by the terminal.draw internally to render the thing
@@ -98,33 +651,120 @@ impl Widget for &App {
 
         /* Creating a line from more spans */
         let instructions = Line::from(vec![
-            " Decrement ".into(),
-            "<Left>".blue().bold(),
-            " Increment ".into(),
-            "<Right>".blue().bold(),
-            " Quit ".into(),
-            "<Q> ".blue().bold(),
+            " Select ".into(),
+            "<↑↓>".blue().bold(),
+            " Change ".into(),
+            "<←→>".blue().bold(),
+            " Set ".into(),
+            "<0-9>".blue().bold(),
+            " Undo ".into(),
+            "<U>".blue().bold(),
+            " Redo ".into(),
+            "<^R>".blue().bold(),
+            " Add ".into(),
+            "<A>".blue().bold(),
+            " Delete ".into(),
+            "<D>".blue().bold(),
+            " Rename ".into(),
+            "<R>".blue().bold(),
+            " Goal ".into(),
+            "<G> ".blue().bold(),
+        ]);
+        let quit = Line::from(vec![" Quit ".into(), "<Q> ".blue().bold()]);
+
+        /* Overflow mode, bounds and step sizes, so the user knows what Left / Right will do */
+        let config = &self.config;
+        let limits = Line::from(vec![
+            " ".into(),
+            config.mode.to_string().magenta(),
+            format!(" {}..{} step {} ", config.min, config.max, config.step).into(),
+            "⇧".blue().bold(),
+            "×10 ".into(),
+            "^".blue().bold(),
+            "×100 ".into(),
         ]);
 
         /* Block is a widget, usully used as a wrapper around lower level ones */
-        let block = Block::bordered()
+        /* Quit and the limits are left out when they would run into the centered title */
+        /* The border flashes green for a moment when a goal is reached */
+        let show_limits = area.width as usize > title.width() + 2 * limits.width() + 2;
+        let mut block = Block::bordered()
             .title(title.centered())
             .title_bottom(instructions.centered())
             .border_set(border::THICK);
+        if show_limits {
+            block = block
+                .title(quit.left_aligned())
+                .title(limits.right_aligned());
+        }
+        if self.flash_until.is_some_and(|until| (self.clock)() < until) {
+            block = block.border_style(Style::new().green().bold());
+        }
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        /* List of counters on the left, sized to the longest name but at most a third of the width */
+        let longest = self.counters.iter()
+            .map(|counter| counter.name.chars().count() + counter.value.to_string().len() + 3)
+            .max()
+            .unwrap_or(0) as u16;
+        let [list_area, value_area] = Layout::horizontal([
+            Constraint::Length((longest + 1).min(inner.width / 3)),
+            Constraint::Min(0),
+        ]).areas(inner);
+
+        let items = self.counters.iter()
+            .map(|counter| ListItem::new(format!("{}: {}", counter.name, counter.value)));
+        let list = List::new(items)
+            .block(Block::new().borders(Borders::RIGHT))
+            .highlight_style(Style::new().reversed());
+        let mut list_state = ListState::default().with_selected(Some(self.selected));
+        StatefulWidget::render(list, list_area, buf, &mut list_state);
 
         /* *Text* represents one or more *Lines* of texts */
-        let counter_text = Text::from(vec![Line::from(vec![
-            "Value: ".into(),
-            self.counter.to_string().yellow()
-        ])]);
+        let counter = self.counter();
+        let mut status_text = Text::from(self.idle_text().dark_gray());
+        if let (Some(message), None) = (&self.message, &self.popup) {
+            status_text.push_line(message.as_str().red());
+        }
+
+        /* The value in big digits if they fit into half of the height the text leaves, */
+        /* the other half is for the history chart */
+        let big_value = BigNumber::new(counter.value).style(Style::new().yellow());
+        let gauge_height = u16::from(counter.goal.is_some());
+        let text_height = 1 + gauge_height + status_text.height() as u16;
+        let big_scale = big_value.scale_to_fit(value_area.width, value_area.height.saturating_sub(text_height) / 2);
+        let value_height = big_scale.map_or(1, |scale| big_value.size(scale).1);
+
+        /* Name, value, goal, status and the history chart below, whatever height is left */
+        /* Rows are taken from the top, so a low area cuts off the bottom parts first */
+        let [name_area, rest] = split_top(value_area, 1);
+        let [number_area, rest] = split_top(rest, value_height);
+        let [gauge_area, rest] = split_top(rest, gauge_height);
+        let [status_area, chart_area] = split_top(rest, status_text.height() as u16);
 
         /* Paragraph is awidget to display text */
-        Paragraph::new(counter_text)
+        Paragraph::new(counter.name.as_str().bold())
             .centered()
-            .block(block) /* Surrounding the Paragraph by a block */
-            .render(area, buf);
+            .render(name_area, buf);
+        if big_scale.is_some() {
+            big_value.render(number_area, buf);
+        } else {
+            /* Too small, the value is a plain line */
+            Paragraph::new(Line::from(vec!["Value: ".into(), counter.value.to_string().yellow()]))
+                .centered()
+                .render(number_area, buf);
+        }
+        self.render_goal(gauge_area, buf);
+        Paragraph::new(status_text)
+            .centered()
+            .render(status_area, buf);
 
+        self.render_history(chart_area, buf);
 
+        if let Some(popup) = &self.popup {
+            self.render_popup(popup, inner, buf);
+        }
 
         /* EXPERIMENTS FROM HERE
         let mut rectangle_test = ratatui::widgets::canvas::Rectangle::default();
@@ -143,6 +783,107 @@ impl Widget for &App {
     }
 }
 
+impl App {
+    /* Progress towards the goal, in green with the time it was reached once it is */
+    fn render_goal(&self, area: Rect, buf: &mut Buffer) {
+        let counter = self.counter();
+        let (Some(goal), Some(progress)) = (counter.goal, counter.goal_progress(self.config.initial_value())) else {
+            return;
+        };
+        let gauge = LineGauge::default()
+            .ratio(progress)
+            .line_set(symbols::line::THICK);
+        let gauge = match counter.reached_at {
+            Some(at) => {
+                let local = at.saturating_add_signed(timer::local_offset(at));
+                gauge
+                    .label(format!("Goal {goal} reached at {}", clock_time(local % (24 * 60 * 60))))
+                    .style(Style::new().green().bold())
+                    .filled_style(Style::new().green())
+            }
+            None => gauge
+                .label(format!("{}/{goal}", counter.value))
+                .filled_style(Style::new().cyan())
+                .unfilled_style(Style::new().dark_gray()),
+        };
+        gauge.render(area, buf);
+    }
+
+    /* Time since the last change of the selected counter, and until its reset if there is one */
+    fn idle_text(&self) -> String {
+        let Some(idle) = self.idle_seconds(self.selected, (self.clock)()) else {
+            return "Not changed yet".to_string();
+        };
+        let mut text = format!("Changed {} ago", timer::format_duration(idle));
+        if let Some(reset_after) = self.config.reset_after
+            && self.counter().value != self.config.initial_value()
+        {
+            text += &format!(", resets in {}", timer::format_duration(reset_after.saturating_sub(idle)));
+        }
+        text
+    }
+
+    /* Changes per bucket of the selected counter, one bar per column, newest on the right */
+    fn render_history(&self, area: Rect, buf: &mut Buffer) {
+        let title = Line::from(vec![
+            format!(" Changes per {} ", self.bucket).into(),
+            "<B> ".blue().bold(),
+        ]);
+        let block = Block::new().borders(Borders::TOP).title(title.centered());
+        let columns = block.inner(area).width as usize;
+        let counts = history::counts(&self.counter().history, self.bucket, (self.clock)(), columns);
+        Sparkline::default()
+            .block(block)
+            .data(&counts)
+            .green()
+            .render(area, buf);
+    }
+
+    /* Small window in the middle, the error of the last attempt is shown at its bottom */
+    fn render_popup(&self, popup: &Popup, area: Rect, buf: &mut Buffer) {
+        let (title, text) = match popup {
+            Popup::Add(name) => (" New counter ", Line::from(name.as_str())),
+            Popup::Rename(name) => (" Rename counter ", Line::from(name.as_str())),
+            Popup::Delete => (
+                " Delete counter ",
+                Line::from(format!("Delete '{}'? (y/n)", self.counter().name))
+            ),
+            Popup::Entry(input) => (" Set value ", Line::from(input.as_str())),
+            Popup::Goal(input) => (" Goal (empty removes it) ", Line::from(input.as_str())),
+        };
+        let mut block = Block::bordered()
+            .title(title.bold())
+            .border_set(border::ROUNDED);
+        if let Some(message) = &self.message {
+            block = block.title_bottom(Line::from(message.as_str().red()));
+        }
+
+        /* Wide enough for the error message */
+        let width = self.message.as_ref().map_or(0, |message| message.chars().count() as u16 + 2).max(40);
+        let popup_area = centered_rect(area, width, 3);
+        Clear.render(popup_area, buf);
+        Paragraph::new(text)
+            .block(block)
+            .render(popup_area, buf);
+    }
+}
+
+/* Splits off the first `height` rows, all of them if the area is lower */
+fn split_top(area: Rect, height: u16) -> [Rect; 2] {
+    let height = height.min(area.height);
+    [
+        Rect { height, ..area },
+        Rect { y: area.y + height, height: area.height - height, ..area },
+    ]
+}
+
+/* Rect of at most width x height in the middle of area */
+fn centered_rect(area: Rect, width: u16, height: u16) -> Rect {
+    let [area] = Layout::horizontal([Constraint::Length(width)]).flex(Flex::Center).areas(area);
+    let [area] = Layout::vertical([Constraint::Length(height)]).flex(Flex::Center).areas(area);
+    area
+}
+
 /*
    -----
    TESTS
@@ -152,31 +893,937 @@ impl Widget for &App {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use ratatui::style::Style;
+    use crate::counter::OverflowMode;
+    use ratatui::style::{Color, Style};
+
+    /* Symbols of one row of the buffer */
+    fn line(buf: &Buffer, y: u16) -> String {
+        (0..buf.area.width).map(|x| buf[(x, y)].symbol()).collect()
+    }
+
+    fn press(app: &mut App, code: KeyCode) {
+        app.handle_key_event(KeyEvent::from(code));
+    }
+
+    fn type_text(app: &mut App, text: &str) {
+        text.chars().for_each(|c| press(app, KeyCode::Char(c)));
+    }
+
+    fn names(app: &App) -> Vec<&str> {
+        app.counters.iter().map(|counter| counter.name.as_str()).collect()
+    }
 
     #[test]
     fn render() {
         let app = App::default();
-        let mut buf = Buffer::empty(Rect::new(0, 0, 50, 4));
+        let mut buf = Buffer::empty(Rect::new(0, 0, 100, 4));
 
         app.render(buf.area, &mut buf);
 
         let mut expected = Buffer::with_lines(vec![
-            "┏━━━━━━━━━━━━━ Counter App Tutorial ━━━━━━━━━━━━━┓",
-            "┃                    Value: 0                    ┃",
-            "┃                                                ┃",
-            "┗━ Decrement <Left> Increment <Right> Quit <Q> ━━┛",
+            "┏ Quit <Q> ━━━━━━━━━━━━━━━━━━━━━━━━━━━━ Counter App Tutorial ━━━ saturate 0..255 step 1 ⇧×10 ^×100 ┓",
+            "┃counter: 0 │                                        counter                                       ┃",
+            "┃           │                                       Value: 0                                       ┃",
+            "┗━━ Select <↑↓> Change <←→> Set <0-9> Undo <U> Redo <^R> Add <A> Delete <D> Rename <R> Goal <G> ━━━┛",
         ]);
         let title_style = Style::new().bold();
+        let selected_style = Style::new().reversed();
+        let name_style = Style::new().bold();
         let counter_style = Style::new().yellow();
         let key_style = Style::new().blue().bold();
-        expected.set_style(Rect::new(14, 0, 22, 1), title_style);
-        expected.set_style(Rect::new(28, 1, 1, 1), counter_style);
-        expected.set_style(Rect::new(13, 3, 6, 1), key_style);
-        expected.set_style(Rect::new(30, 3, 7, 1), key_style);
-        expected.set_style(Rect::new(43, 3, 4, 1), key_style);
+        let mode_style = Style::new().magenta();
+        expected.set_style(Rect::new(7, 0, 4, 1), key_style);
+        expected.set_style(Rect::new(39, 0, 22, 1), title_style);
+        expected.set_style(Rect::new(65, 0, 8, 1), mode_style);
+        expected.set_style(Rect::new(88, 0, 1, 1), key_style);
+        expected.set_style(Rect::new(93, 0, 1, 1), key_style);
+        expected.set_style(Rect::new(1, 1, 11, 1), selected_style);
+        expected.set_style(Rect::new(53, 1, 7, 1), name_style);
+        expected.set_style(Rect::new(59, 2, 1, 1), counter_style);
+        for (x, width) in [(11, 4), (23, 4), (32, 5), (43, 3), (52, 4), (61, 3), (72, 3), (83, 3), (92, 4)] {
+            expected.set_style(Rect::new(x, 3, width, 1), key_style);
+        }
 
         assert_eq!(buf, expected);
     }
-}
 
+    #[test]
+    fn render_rejected_change() {
+        let mut app = App::new(CounterConfig { mode: OverflowMode::Reject, ..Default::default() });
+        app.decrement_counter(1);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 100, 6));
+
+        app.render(buf.area, &mut buf);
+
+        assert_eq!(line(&buf, 3), "┃           │                                    Not changed yet                                   ┃");
+        assert_eq!(line(&buf, 4), "┃           │                                  Can not go below 0                                  ┃");
+        assert_eq!(buf[(47, 4)].fg, Color::Red);
+        assert!(line(&buf, 0).contains(" reject 0..255 step 1 "));
+    }
+
+    #[test]
+    fn saturate_at_both_ends() {
+        let mut app = App::default();
+        app.handle_key_event(KeyEvent::from(KeyCode::Left));
+        assert_eq!(app.counter().value, 0);
+        assert_eq!(app.message, None);
+
+        app.counters[0].value = 255;
+        app.handle_key_event(KeyEvent::from(KeyCode::Right));
+        assert_eq!(app.counter().value, 255);
+    }
+
+    #[test]
+    fn wrap_at_both_ends() {
+        let mut app = App::new(CounterConfig { mode: OverflowMode::Wrap, ..Default::default() });
+        app.handle_key_event(KeyEvent::from(KeyCode::Left));
+        assert_eq!(app.counter().value, 255);
+        app.handle_key_event(KeyEvent::from(KeyCode::Right));
+        assert_eq!(app.counter().value, 0);
+    }
+
+    #[test]
+    fn reject_shows_a_message_until_the_next_change() {
+        let mut app = App::new(CounterConfig { max: 2, mode: OverflowMode::Reject, ..Default::default() });
+        app.handle_key_event(KeyEvent::from(KeyCode::Left));
+        assert_eq!(app.counter().value, 0);
+        assert_eq!(app.message.as_deref(), Some("Can not go below 0"));
+
+        app.handle_key_event(KeyEvent::from(KeyCode::Right));
+        assert_eq!(app.counter().value, 1);
+        assert_eq!(app.message, None);
+    }
+
+    #[test]
+    fn step_and_initial_value() {
+        let mut app = App::new(CounterConfig { min: 10, max: 100, step: 25, ..Default::default() });
+        assert_eq!(app.counter().value, 10);
+        app.increment_counter(1);
+        app.increment_counter(1);
+        assert_eq!(app.counter().value, 60);
+        app.increment_counter(1);
+        app.increment_counter(1);
+        assert_eq!(app.counter().value, 100);
+    }
+
+    #[test]
+    fn render_counter_list_and_popup() {
+        let mut app = App::default();
+        app.add_counter("reviews").unwrap();
+        app.increment_counter(1);
+        press(&mut app, KeyCode::Char('a'));
+        type_text(&mut app, "counter");
+        press(&mut app, KeyCode::Enter);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 100, 8));
+
+        app.render(buf.area, &mut buf);
+
+        assert_eq!(line(&buf, 1), "┃counter: 0 │                                        reviews                                       ┃");
+        assert_eq!(line(&buf, 2), "┃reviews: 1 │                                       Value: 1                                       ┃");
+        assert_eq!(line(&buf, 3), "┃           │               ╭ New counter ─────────────────────────────╮                           ┃");
+        assert_eq!(line(&buf, 4), "┃           │───────────────│counter                                   │───────────────────────────┃");
+        /* The increment of reviews is the last bar of its history */
+        assert_eq!(line(&buf, 5), "┃           │               ╰There is already a counter named 'counter'╯                          █┃");
+        assert_eq!(buf[(1, 2)].modifier, Style::new().reversed().add_modifier);
+    }
+
+    #[test]
+    fn select_and_change_each_counter() {
+        let mut app = App::default();
+        app.add_counter("reviews").unwrap();
+        assert_eq!(app.selected, 1);
+
+        press(&mut app, KeyCode::Right);
+        press(&mut app, KeyCode::Right);
+        press(&mut app, KeyCode::Up);
+        press(&mut app, KeyCode::Up);
+        press(&mut app, KeyCode::Right);
+        assert_eq!(app.selected, 0);
+        assert_eq!(app.counters[0].value, 1);
+        assert_eq!(app.counters[1].value, 2);
+
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Left);
+        assert_eq!(app.selected, 1);
+        assert_eq!(app.counters[1].value, 1);
+    }
+
+    #[test]
+    fn add_counter_through_popup() {
+        let mut app = App::default();
+        press(&mut app, KeyCode::Char('a'));
+        type_text(&mut app, "pomodorox");
+        press(&mut app, KeyCode::Backspace);
+        /* Keys go to the popup while it is open */
+        type_text(&mut app, "q");
+        press(&mut app, KeyCode::Backspace);
+        press(&mut app, KeyCode::Enter);
+
+        assert!(app.popup.is_none());
+        assert!(!app.exit);
+        assert_eq!(names(&app), ["counter", "pomodoro"]);
+        assert_eq!(app.selected, 1);
+    }
+
+    #[test]
+    fn invalid_names_keep_the_popup_open() {
+        let mut app = App::default();
+        press(&mut app, KeyCode::Char('a'));
+        type_text(&mut app, "  ");
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.popup, Some(Popup::Add("  ".to_string())));
+        assert_eq!(app.message.as_deref(), Some("Name can not be empty"));
+
+        press(&mut app, KeyCode::Esc);
+        assert!(app.popup.is_none());
+        assert_eq!(app.message, None);
+        assert_eq!(names(&app), ["counter"]);
+    }
+
+    #[test]
+    fn rename_through_popup() {
+        let mut app = App::default();
+        app.add_counter("reviews").unwrap();
+        press(&mut app, KeyCode::Char('r'));
+        assert_eq!(app.popup, Some(Popup::Rename("reviews".to_string())));
+
+        /* Renaming onto another counter's name is refused */
+        (0..7).for_each(|_| press(&mut app, KeyCode::Backspace));
+        type_text(&mut app, "counter");
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.message.as_deref(), Some("There is already a counter named 'counter'"));
+
+        (0..7).for_each(|_| press(&mut app, KeyCode::Backspace));
+        type_text(&mut app, "incidents");
+        press(&mut app, KeyCode::Enter);
+        assert!(app.popup.is_none());
+        assert_eq!(names(&app), ["counter", "incidents"]);
+    }
+
+    #[test]
+    fn delete_asks_first_and_keeps_the_last_counter() {
+        let mut app = App::default();
+        app.add_counter("reviews").unwrap();
+        app.add_counter("incidents").unwrap();
+        app.selected = 1;
+
+        press(&mut app, KeyCode::Char('d'));
+        press(&mut app, KeyCode::Char('n'));
+        assert_eq!(names(&app), ["counter", "reviews", "incidents"]);
+
+        press(&mut app, KeyCode::Char('d'));
+        press(&mut app, KeyCode::Char('y'));
+        assert_eq!(names(&app), ["counter", "incidents"]);
+        assert_eq!(app.selected, 1);
+
+        press(&mut app, KeyCode::Char('d'));
+        press(&mut app, KeyCode::Char('y'));
+        assert_eq!(names(&app), ["counter"]);
+        assert_eq!(app.selected, 0);
+
+        press(&mut app, KeyCode::Char('d'));
+        press(&mut app, KeyCode::Char('y'));
+        assert_eq!(names(&app), ["counter"]);
+        assert_eq!(app.popup, Some(Popup::Delete));
+        assert_eq!(app.message.as_deref(), Some("Can not delete the last counter"));
+    }
+
+    /* State file in a fresh temp directory, removed by the test */
+    fn temp_state_file(name: &str) -> (PathBuf, StateFile) {
+        let dir = env::temp_dir().join(format!("counter-app-main-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let file = StateFile::new(dir.join("counters.json"));
+        (dir, file)
+    }
+
+    #[test]
+    fn every_change_is_saved_and_restored() {
+        let (dir, file) = temp_state_file("changes");
+        let path = file.path().to_path_buf();
+        let mut app = App::default();
+        app.load_state(file);
+        assert!(app.message.as_deref().unwrap().starts_with("No saved counters"));
+
+        app.handle_event(Event::Key(KeyEvent::from(KeyCode::Right)));
+        app.handle_event(Event::Key(KeyEvent::from(KeyCode::Char('a'))));
+        "reviews".chars().for_each(|c| app.handle_event(Event::Key(KeyEvent::from(KeyCode::Char(c)))));
+        /* Only typed into the popup so far, the file still has one counter */
+        let saved: SavedState = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved.counters, app.counters[..1]);
+        assert_eq!(saved.counters[0].value, 1);
+
+        app.handle_event(Event::Key(KeyEvent::from(KeyCode::Enter)));
+        app.handle_event(Event::Key(KeyEvent::from(KeyCode::Right)));
+
+        let mut restored = App::default();
+        restored.load_state(StateFile::new(path));
+        assert_eq!(restored.counters, app.counters);
+        assert_eq!(names(&restored), ["counter", "reviews"]);
+        assert_eq!(restored.selected, 1);
+        assert_eq!(restored.message, None);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn only_changes_are_saved() {
+        let (dir, file) = temp_state_file("unchanged");
+        let path = file.path().to_path_buf();
+        let mut app = App::default();
+        app.load_state(file);
+        app.handle_event(Event::Key(KeyEvent::from(KeyCode::Right)));
+        assert!(path.exists());
+
+        /* Ticks and keys that change nothing leave the file alone */
+        std::fs::remove_file(&path).unwrap();
+        app.tick();
+        app.handle_event(Event::Key(KeyEvent::from(KeyCode::Up)));
+        app.handle_event(Event::Key(KeyEvent::from(KeyCode::Char('a'))));
+        assert!(!path.exists());
+
+        app.handle_event(Event::Key(KeyEvent::from(KeyCode::Esc)));
+        app.handle_event(Event::Key(KeyEvent::from(KeyCode::Right)));
+        assert!(path.exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn restored_values_respect_current_bounds() {
+        let (dir, file) = temp_state_file("bounds");
+        file.save(&SavedState {
+            counters: vec![Counter::new("low", -5), Counter::new("high", 500)],
+            selected: 7,
+        }).unwrap();
+
+        let mut app = App::default();
+        app.load_state(file);
+        assert_eq!(app.counters, [Counter::new("low", 0), Counter::new("high", 255)]);
+        assert_eq!(app.selected, 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn failed_save_is_reported() {
+        /* The state file would have to go inside a plain file */
+        let (dir, _) = temp_state_file("unsaveable");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("blocker"), "").unwrap();
+        let mut app = App::default();
+        app.load_state(StateFile::new(dir.join("blocker").join("counters.json")));
+
+        /* While running the error is the message */
+        app.handle_event(Event::Key(KeyEvent::from(KeyCode::Right)));
+        assert!(app.message.as_deref().unwrap().starts_with("Could not save"));
+        /* On exit it is returned, as nothing draws the message anymore */
+        assert!(app.save_state().unwrap_err().to_string().starts_with("Could not save"));
+        assert!(App::default().save_state().is_ok());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn corrupt_state_falls_back_to_defaults_with_warning() {
+        let (dir, file) = temp_state_file("corrupt");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(file.path(), "[1, 2").unwrap();
+
+        let mut app = App::default();
+        app.load_state(file);
+        assert_eq!(app.counters, [Counter::new("counter", 0)]);
+        assert!(app.message.as_deref().unwrap().contains("is corrupt"));
+
+        /* The warning is rendered under the value */
+        let mut buf = Buffer::empty(Rect::new(0, 0, 100, 6));
+        app.render(buf.area, &mut buf);
+        assert!(line(&buf, 4).contains("is corrupt"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn take_state_flag() {
+        let mut args: Vec<String> = ["--max", "9", "--state", "x.json"].map(String::from).into();
+        assert_eq!(take_flag(&mut args, "--state"), Ok(Some("x.json".to_string())));
+        assert_eq!(args, ["--max", "9"]);
+        assert_eq!(take_flag(&mut args, "--state"), Ok(None));
+
+        let mut args: Vec<String> = vec!["--state".to_string()];
+        assert!(take_flag(&mut args, "--state").is_err());
+    }
+
+    #[test]
+    fn changes_are_recorded_with_their_time() {
+        let mut app = App { clock: || 1_000, ..Default::default() };
+        press(&mut app, KeyCode::Right);
+        press(&mut app, KeyCode::Right);
+        app.clock = || 1_090;
+        press(&mut app, KeyCode::Left);
+        /* Saturated at 0, nothing moved */
+        press(&mut app, KeyCode::Left);
+        press(&mut app, KeyCode::Left);
+
+        assert_eq!(app.counter().history, [
+            Change { at: 1_000, value: 1 },
+            Change { at: 1_000, value: 2 },
+            Change { at: 1_090, value: 1 },
+            Change { at: 1_090, value: 0 },
+        ]);
+    }
+
+    #[test]
+    fn render_history_and_cycle_bucket() {
+        let mut app = App { clock: || 0, ..Default::default() };
+        press(&mut app, KeyCode::Right);
+        app.clock = || 60;
+        (0..3).for_each(|_| press(&mut app, KeyCode::Right));
+        app.clock = || 179;
+        (0..4).for_each(|_| press(&mut app, KeyCode::Right));
+        let mut buf = Buffer::empty(Rect::new(0, 0, 40, 9));
+
+        app.render(buf.area, &mut buf);
+
+        assert_eq!(line(&buf, 3), "┃           │      Changed 0s ago      ┃");
+        /* 1, 3 and 4 changes in the last three minutes */
+        assert_eq!(line(&buf, 4), "┃           │─ Changes per minute <B> ─┃");
+        assert_eq!(line(&buf, 5), "┃           │                        ▂█┃");
+        assert_eq!(line(&buf, 6), "┃           │                        ██┃");
+        assert_eq!(line(&buf, 7), "┃           │                       ▆██┃");
+        assert_eq!(buf[(37, 6)].fg, Color::Green);
+
+        press(&mut app, KeyCode::Char('b'));
+        let mut buf = Buffer::empty(Rect::new(0, 0, 40, 9));
+        app.render(buf.area, &mut buf);
+
+        /* All of them in the current hour */
+        assert_eq!(line(&buf, 4), "┃           │── Changes per hour <B> ──┃");
+        assert_eq!(line(&buf, 5), "┃           │                         █┃");
+        assert_eq!(line(&buf, 7), "┃           │                         █┃");
+    }
+
+    fn press_with(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
+        app.handle_key_event(KeyEvent::new(code, modifiers));
+    }
+
+    #[test]
+    fn shift_and_ctrl_take_bigger_steps() {
+        let mut app = App::new(CounterConfig { max: 1_000, ..Default::default() });
+        press_with(&mut app, KeyCode::Right, KeyModifiers::SHIFT);
+        assert_eq!(app.counter().value, 10);
+        press_with(&mut app, KeyCode::Right, KeyModifiers::CONTROL);
+        assert_eq!(app.counter().value, 110);
+        press_with(&mut app, KeyCode::Left, KeyModifiers::CONTROL | KeyModifiers::SHIFT);
+        assert_eq!(app.counter().value, 10);
+        press_with(&mut app, KeyCode::Left, KeyModifiers::SHIFT);
+        press_with(&mut app, KeyCode::Left, KeyModifiers::SHIFT);
+        assert_eq!(app.counter().value, 0);
+
+        /* The multiplier applies to the configured step */
+        let mut app = App::new(CounterConfig { max: 1_000, step: 5, ..Default::default() });
+        press_with(&mut app, KeyCode::Right, KeyModifiers::SHIFT);
+        assert_eq!(app.counter().value, 50);
+    }
+
+    #[test]
+    fn digits_open_the_entry_popup() {
+        let mut app = App::default();
+        type_text(&mut app, "4");
+        assert_eq!(app.popup, Some(Popup::Entry("4".to_string())));
+        /* Letters and a minus after the first digit are ignored */
+        type_text(&mut app, "2x-");
+        assert_eq!(app.popup, Some(Popup::Entry("42".to_string())));
+        let mut buf = Buffer::empty(Rect::new(0, 0, 60, 7));
+        app.render(buf.area, &mut buf);
+        assert_eq!(line(&buf, 2), "┃         ╭ Set value ───────────────────────────╮         ┃");
+        assert_eq!(line(&buf, 3), "┃         │42                                    │         ┃");
+        assert_eq!(line(&buf, 4), "┃         ╰──────────────────────────────────────╯─────────┃");
+
+        press(&mut app, KeyCode::Enter);
+        assert!(app.popup.is_none());
+        assert_eq!(app.counter().value, 42);
+    }
+
+    #[test]
+    fn entered_values_have_to_be_inside_the_bounds() {
+        let mut app = App::new(CounterConfig { min: -10, max: 10, ..Default::default() });
+        type_text(&mut app, "11");
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.message.as_deref(), Some("Value has to be between -10 and 10"));
+        assert_eq!(app.counter().value, 0);
+
+        (0..2).for_each(|_| press(&mut app, KeyCode::Backspace));
+        type_text(&mut app, "-");
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.message.as_deref(), Some("'-' is not a whole number"));
+
+        type_text(&mut app, "7");
+        press(&mut app, KeyCode::Enter);
+        assert!(app.popup.is_none());
+        assert_eq!(app.counter().value, -7);
+    }
+
+    #[test]
+    fn undo_and_redo_value_changes() {
+        let mut app = App::default();
+        app.add_counter("reviews").unwrap();
+        press(&mut app, KeyCode::Right);
+        type_text(&mut app, "9");
+        press(&mut app, KeyCode::Enter);
+        press(&mut app, KeyCode::Up);
+        press(&mut app, KeyCode::Right);
+        assert_eq!(app.counters[0].value, 1);
+        assert_eq!(app.counters[1].value, 9);
+
+        press(&mut app, KeyCode::Char('u'));
+        assert_eq!(app.counters[0].value, 0);
+        /* Undo selects the counter it changed */
+        press(&mut app, KeyCode::Char('u'));
+        assert_eq!(app.selected, 1);
+        assert_eq!(app.counters[1].value, 1);
+
+        press_with(&mut app, KeyCode::Char('r'), KeyModifiers::CONTROL);
+        assert_eq!(app.counters[1].value, 9);
+        assert!(app.popup.is_none());
+
+        /* A new change drops what could have been redone */
+        press(&mut app, KeyCode::Left);
+        press_with(&mut app, KeyCode::Char('r'), KeyModifiers::CONTROL);
+        assert_eq!(app.counters[1].value, 8);
+        assert_eq!(app.message.as_deref(), Some("Nothing to redo"));
+
+        (0..3).for_each(|_| press(&mut app, KeyCode::Char('u')));
+        assert_eq!(app.counters[1].value, 0);
+        press(&mut app, KeyCode::Char('u'));
+        assert_eq!(app.message.as_deref(), Some("Nothing to undo"));
+    }
+
+    #[test]
+    fn undo_drops_the_oldest_edits() {
+        let mut app = App::new(CounterConfig { max: 10_000, ..Default::default() });
+        (0..MAX_UNDO + 5).for_each(|_| press(&mut app, KeyCode::Right));
+        assert_eq!(app.undo.len(), MAX_UNDO);
+
+        (0..MAX_UNDO).for_each(|_| press(&mut app, KeyCode::Char('u')));
+        assert_eq!(app.counter().value, 5);
+        press(&mut app, KeyCode::Char('u'));
+        assert_eq!(app.message.as_deref(), Some("Nothing to undo"));
+    }
+
+    #[test]
+    fn deleting_a_counter_forgets_the_undo_stack() {
+        let mut app = App::default();
+        app.add_counter("reviews").unwrap();
+        press(&mut app, KeyCode::Right);
+        press(&mut app, KeyCode::Up);
+        press(&mut app, KeyCode::Char('d'));
+        press(&mut app, KeyCode::Char('y'));
+
+        press(&mut app, KeyCode::Char('u'));
+        assert_eq!(app.message.as_deref(), Some("Nothing to undo"));
+        assert_eq!(app.counters[0].value, 1);
+    }
+
+    #[test]
+    fn idle_counters_are_reset() {
+        let config = CounterConfig { reset_after: Some(600), ..Default::default() };
+        let mut app = App { clock: || 1_000, ..App::new(config) };
+        app.add_counter("reviews").unwrap();
+        press(&mut app, KeyCode::Right);
+        app.clock = || 1_300;
+        press(&mut app, KeyCode::Up);
+        press(&mut app, KeyCode::Right);
+        press(&mut app, KeyCode::Right);
+
+        app.clock = || 1_599;
+        app.tick();
+        assert_eq!((app.counters[0].value, app.counters[1].value), (2, 1));
+        let mut buf = Buffer::empty(Rect::new(0, 0, 60, 5));
+        app.render(buf.area, &mut buf);
+        assert_eq!(line(&buf, 3), "┃           │     Changed 4m 59s ago, resets in 5m 01s     ┃");
+
+        app.clock = || 1_600;
+        app.tick();
+        assert_eq!((app.counters[0].value, app.counters[1].value), (2, 0));
+        assert_eq!(app.message.as_deref(), Some("'reviews' was reset after 10m 00s without changes"));
+
+        /* The reset is a change of its own, so it does not repeat, and it can be undone */
+        app.clock = || 2_500;
+        app.tick();
+        assert_eq!((app.counters[0].value, app.counters[1].value), (0, 0));
+        press(&mut app, KeyCode::Char('u'));
+        press(&mut app, KeyCode::Char('u'));
+        assert_eq!((app.counters[0].value, app.counters[1].value), (2, 1));
+    }
+
+    #[test]
+    fn counters_are_reset_at_the_time_of_day() {
+        let config = CounterConfig { reset_at: Some(6 * 3600), ..Default::default() };
+        let mut app = App { clock: || 1_000, ..App::new(config) };
+        press(&mut app, KeyCode::Right);
+
+        /* The first tick only notes the time */
+        app.tick();
+        assert_eq!(app.counter().value, 1);
+        app.clock = || 1_001;
+        app.tick();
+        assert_eq!(app.counter().value, 1);
+
+        /* Whatever the time zone, 06:00 came once during a whole day */
+        app.clock = || 1_001 + 24 * 3600;
+        app.tick();
+        assert_eq!(app.counter().value, 0);
+        assert_eq!(app.message.as_deref(), Some("'counter' was reset at 06:00"));
+    }
+
+    #[test]
+    fn idle_text() {
+        let mut app = App { clock: || 100, ..Default::default() };
+        assert_eq!(app.idle_text(), "Not changed yet");
+        press(&mut app, KeyCode::Right);
+        app.clock = || 292;
+        assert_eq!(app.idle_text(), "Changed 3m 12s ago");
+    }
+
+    fn render_value(value: i64, width: u16, height: u16) -> Buffer {
+        let mut app = App { clock: || 100, ..Default::default() };
+        app.counters[0].value = value;
+        let mut buf = Buffer::empty(Rect::new(0, 0, width, height));
+        app.render(buf.area, &mut buf);
+        buf
+    }
+
+    #[test]
+    fn render_big_value() {
+        let buf = render_value(128, 60, 10);
+
+        assert_eq!(line(&buf, 1), "┃counter: 128 │                   counter                  ┃");
+        assert_eq!(line(&buf, 2), "┃             │                ▄█  ▀▀█ █▀█                 ┃");
+        assert_eq!(line(&buf, 3), "┃             │                 █  █▀▀ █▀█                 ┃");
+        assert_eq!(line(&buf, 4), "┃             │                ▀▀▀ ▀▀▀ ▀▀▀                 ┃");
+        assert_eq!(line(&buf, 5), "┃             │               Not changed yet              ┃");
+        assert_eq!(line(&buf, 6), "┃             │────────── Changes per minute <B> ──────────┃");
+        assert_eq!(buf[(31, 2)].fg, Color::Yellow);
+    }
+
+    #[test]
+    fn render_big_value_scales_with_the_area() {
+        let buf = render_value(128, 80, 24);
+
+        assert_eq!(line(&buf, 2), "┃             │              ████        ████████████    ████████████          ┃");
+        assert_eq!(line(&buf, 4), "┃             │          ████████                ████    ████    ████          ┃");
+        assert_eq!(line(&buf, 11), "┃             │          ████████████    ████████████    ████████████          ┃");
+        assert_eq!(line(&buf, 12), "┃             │                         Not changed yet                        ┃");
+        assert_eq!(line(&buf, 13), "┃             │──────────────────── Changes per minute <B> ────────────────────┃");
+    }
+
+    #[test]
+    fn render_plain_value_when_big_digits_do_not_fit() {
+        /* Too low */
+        let buf = render_value(128, 60, 6);
+        assert_eq!(line(&buf, 2), "┃             │                 Value: 128                 ┃");
+        assert_eq!(line(&buf, 3), "┃             │               Not changed yet              ┃");
+
+        /* Too narrow for all the digits */
+        let buf = render_value(i64::MIN, 100, 30);
+        assert!(line(&buf, 2).contains("Value: -9223372036854775808"));
+        let buf = render_value(-7, 100, 30);
+        assert!(!line(&buf, 2).contains("Value:"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn commands_change_the_named_or_selected_counter() {
+        let mut app = App::new(CounterConfig { max: 10, ..Default::default() });
+        app.add_counter("commits").unwrap();
+        app.selected = 0;
+
+        assert_eq!(app.handle_command(Command::Increment(None)), "ok 1");
+        assert_eq!(app.handle_command(Command::Increment(Some("commits".to_string()))), "ok 1");
+        assert_eq!(app.handle_command(Command::Set(7, Some("commits".to_string()))), "ok 7");
+        assert_eq!(app.handle_command(Command::Decrement(Some("commits".to_string()))), "ok 6");
+        assert_eq!(app.handle_command(Command::Get(None)), "ok 1");
+        assert_eq!((app.counters[0].value, app.counters[1].value), (1, 6));
+        assert_eq!(app.selected, 0);
+
+        assert_eq!(app.handle_command(Command::Set(11, None)), "error Value has to be between 0 and 10");
+        assert_eq!(app.handle_command(Command::Get(Some("bugs".to_string()))), "error There is no counter named 'bugs'");
+        /* Changes from the socket can be undone like any other */
+        press(&mut app, KeyCode::Char('u'));
+        assert_eq!(app.counters[1].value, 7);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn control_socket_drives_the_app() {
+        use std::io::{BufRead, BufReader, Write};
+        use std::os::unix::net::UnixStream;
+
+        let path = env::temp_dir().join(format!("counter-app-main-socket-{}.sock", std::process::id()));
+        let (dir, file) = temp_state_file("socket");
+        let (sender, events) = mpsc::channel();
+        let socket = ControlSocket::bind(path.clone(), sender).unwrap();
+        let mut app = App::default();
+        app.load_state(file);
+
+        let client = thread::spawn(move || {
+            let mut stream = UnixStream::connect(&path).unwrap();
+            stream.write_all(b"inc\ninc\nset 200\ndec\nget\n").unwrap();
+            BufReader::new(stream).lines().take(5).map(Result::unwrap).collect::<Vec<_>>()
+        });
+        /* What the main loop does, one event per command */
+        for _ in 0..5 {
+            let event = events.recv_timeout(Duration::from_secs(5)).unwrap();
+            app.handle_app_event(event).unwrap();
+        }
+
+        assert_eq!(client.join().unwrap(), ["ok 1", "ok 2", "ok 200", "ok 199", "ok 199"]);
+        /* Every change was saved as it happened */
+        let saved: SavedState = serde_json::from_str(&std::fs::read_to_string(dir.join("counters.json")).unwrap()).unwrap();
+        assert_eq!(saved.counters[0].value, 199);
+        drop(socket);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /* Values around the interesting edges: sign, digit count and the i64 limits */
+    const VALUES: [i64; 10] = [i64::MIN, -1_000_000, -10, -1, 0, 1, 9, 10, 255, i64::MAX];
+
+    /* Is the value on screen, as the plain line or in big digits at any scale */
+    fn shows_value(buf: &Buffer, value: i64) -> bool {
+        let rows: Vec<String> = (0..buf.area.height).map(|y| line(buf, y)).collect();
+        if rows.iter().any(|row| row.contains(&format!("Value: {value}"))) {
+            return true;
+        }
+        let big = BigNumber::new(value);
+        let largest = big.scale_to_fit(buf.area.width, buf.area.height).unwrap_or(0);
+        (1..=largest).any(|scale| {
+            let lines = big.lines(scale);
+            rows.windows(lines.len()).any(|window| {
+                window.iter().zip(&lines).all(|(row, big_line)| row.contains(big_line.as_str()))
+            })
+        })
+    }
+
+    #[test]
+    fn render_any_size_and_value() {
+        let wide_config = CounterConfig { min: i64::MIN, max: i64::MAX, ..Default::default() };
+        for value in VALUES {
+            for width in (0..=130).step_by(13) {
+                for height in [0, 1, 2, 3, 4, 5, 6, 8, 10, 12, 16, 20, 30] {
+                    let mut app = App { clock: || 100, ..App::new(wide_config.clone()) };
+                    app.counters[0].value = value;
+                    let mut buf = Buffer::empty(Rect::new(0, 0, width, height));
+                    app.render(buf.area, &mut buf);
+
+                    /* Two inner rows and room for the plain line next to the list are enough */
+                    let plain_width = 2 + width.saturating_sub(2) / 3 + format!("Value: {value}").len() as u16;
+                    if height >= 4 && width >= plain_width + 20 {
+                        assert!(shows_value(&buf, value), "{value} missing at {width}x{height}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn render_any_size_with_popup_and_message() {
+        let message = "There is already a counter named 'counter'";
+        let popups = [
+            (Popup::Add("new counter".to_string()), "New counter"),
+            (Popup::Delete, "Delete counter"),
+            (Popup::Entry("-12".to_string()), "Set value"),
+            (Popup::Goal(String::new()), "Goal (empty removes it)"),
+        ];
+        for (popup, title) in popups {
+            for width in (0..=60).step_by(3) {
+                for height in [0, 1, 2, 3, 4, 5, 8, 20] {
+                    let app = App { popup: Some(popup.clone()), message: Some(message.to_string()), ..Default::default() };
+                    let area = Rect::new(0, 0, width, height);
+                    let mut buf = Buffer::empty(area);
+                    app.render(area, &mut buf);
+
+                    assert_eq!(buf.area, area);
+                    let text: String = (0..height).map(|y| line(&buf, y)).collect::<Vec<_>>().join("\n");
+                    /* The popup is sized to the message and sits inside the border, once it fits the title
+                       and the message show in full */
+                    if width as usize >= message.len() + 4 && height >= 5 {
+                        assert!(text.contains(title), "{width}x{height} misses '{title}':\n{text}");
+                        assert!(text.contains(message), "{width}x{height} misses the message:\n{text}");
+                    }
+                    if width == 0 || height == 0 {
+                        assert_eq!(buf, Buffer::empty(area));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn render_degenerate_areas() {
+        let app = App::default();
+        for area in [Rect::new(0, 0, 0, 0), Rect::new(0, 0, 80, 0), Rect::new(0, 0, 0, 20)] {
+            let mut buf = Buffer::empty(area);
+            app.render(area, &mut buf);
+            assert_eq!(buf, Buffer::empty(area));
+        }
+
+        /* Only the borders fit */
+        let mut buf = Buffer::empty(Rect::new(0, 0, 6, 2));
+        app.render(buf.area, &mut buf);
+        assert_eq!(line(&buf, 0), "┏App ┓");
+        assert_eq!(line(&buf, 1), "┗Redo┛");
+
+        /* Narrow: the list gets a third, the text is cut */
+        let mut buf = Buffer::empty(Rect::new(0, 0, 14, 5));
+        app.render(buf.area, &mut buf);
+        assert_eq!(line(&buf, 1), "┃cou│ counter┃");
+        assert_eq!(line(&buf, 2), "┃   │Value: 0┃");
+        assert_eq!(line(&buf, 3), "┃   │Not chan┃");
+    }
+
+    /* Key events and the state they should leave behind */
+    #[test]
+    fn key_sequences() {
+        struct Case {
+            keys: &'static [KeyCode],
+            values: &'static [i64],
+            selected: usize,
+            popup: Option<Popup>,
+            exit: bool,
+        }
+        let cases = [
+            Case { keys: &[], values: &[0], selected: 0, popup: None, exit: false },
+            Case { keys: &[KeyCode::Char('q')], values: &[0], selected: 0, popup: None, exit: true },
+            Case { keys: &[KeyCode::Right, KeyCode::Right, KeyCode::Left], values: &[1], selected: 0, popup: None, exit: false },
+            /* Keys without a meaning change nothing */
+            Case { keys: &[KeyCode::Esc, KeyCode::Enter, KeyCode::Char('z'), KeyCode::Tab], values: &[0], selected: 0, popup: None, exit: false },
+            /* Selection stops at both ends */
+            Case { keys: &[KeyCode::Up, KeyCode::Down, KeyCode::Down], values: &[0], selected: 0, popup: None, exit: false },
+            Case { keys: &[KeyCode::Char('a')], values: &[0], selected: 0, popup: Some(Popup::Add(String::new())), exit: false },
+            /* q is typed into the popup, not quitting */
+            Case { keys: &[KeyCode::Char('a'), KeyCode::Char('q')], values: &[0], selected: 0, popup: Some(Popup::Add("q".to_string())), exit: false },
+            Case { keys: &[KeyCode::Char('a'), KeyCode::Char('x'), KeyCode::Enter, KeyCode::Right], values: &[0, 1], selected: 1, popup: None, exit: false },
+            Case { keys: &[KeyCode::Char('a'), KeyCode::Char('x'), KeyCode::Esc, KeyCode::Right], values: &[1], selected: 0, popup: None, exit: false },
+            Case { keys: &[KeyCode::Char('d'), KeyCode::Char('y')], values: &[0], selected: 0, popup: Some(Popup::Delete), exit: false },
+            Case { keys: &[KeyCode::Char('d'), KeyCode::Char('x')], values: &[0], selected: 0, popup: Some(Popup::Delete), exit: false },
+            Case { keys: &[KeyCode::Char('r')], values: &[0], selected: 0, popup: Some(Popup::Rename("counter".to_string())), exit: false },
+            Case { keys: &[KeyCode::Char('5'), KeyCode::Backspace, KeyCode::Char('7'), KeyCode::Enter], values: &[7], selected: 0, popup: None, exit: false },
+            Case { keys: &[KeyCode::Char('5'), KeyCode::Esc], values: &[0], selected: 0, popup: None, exit: false },
+            Case { keys: &[KeyCode::Right, KeyCode::Char('u'), KeyCode::Char('u')], values: &[0], selected: 0, popup: None, exit: false },
+            Case { keys: &[KeyCode::Char('b'), KeyCode::Char('b')], values: &[0], selected: 0, popup: None, exit: false },
+        ];
+
+        for case in cases {
+            let mut app = App::default();
+            case.keys.iter().for_each(|&code| press(&mut app, code));
+
+            let values: Vec<i64> = app.counters.iter().map(|counter| counter.value).collect();
+            assert_eq!(values, case.values, "values after {:?}", case.keys);
+            assert_eq!(app.selected, case.selected, "selected after {:?}", case.keys);
+            assert_eq!(app.popup, case.popup, "popup after {:?}", case.keys);
+            assert_eq!(app.exit, case.exit, "exit after {:?}", case.keys);
+        }
+    }
+
+    #[test]
+    fn bucket_key_cycles() {
+        let mut app = App::default();
+        assert_eq!(app.bucket, Bucket::Minute);
+        press(&mut app, KeyCode::Char('b'));
+        assert_eq!(app.bucket, Bucket::Hour);
+        press(&mut app, KeyCode::Char('b'));
+        press(&mut app, KeyCode::Char('b'));
+        assert_eq!(app.bucket, Bucket::Minute);
+    }
+
+    #[test]
+    fn only_key_presses_count() {
+        let mut app = App::default();
+        let mut release = KeyEvent::from(KeyCode::Right);
+        release.kind = KeyEventKind::Release;
+        app.handle_event(Event::Key(release));
+        app.handle_event(Event::FocusLost);
+        app.handle_event(Event::Resize(10, 10));
+        assert_eq!(app.counter().value, 0);
+
+        app.handle_event(Event::Key(KeyEvent::from(KeyCode::Right)));
+        assert_eq!(app.counter().value, 1);
+    }
+
+    #[test]
+    fn goal_through_popup() {
+        let mut app = App::default();
+        press(&mut app, KeyCode::Char('g'));
+        assert_eq!(app.popup, Some(Popup::Goal(String::new())));
+        type_text(&mut app, "300");
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.message.as_deref(), Some("Value has to be between 0 and 255"));
+
+        press(&mut app, KeyCode::Backspace);
+        press(&mut app, KeyCode::Backspace);
+        press(&mut app, KeyCode::Enter);
+        assert!(app.popup.is_none());
+        assert_eq!(app.counter().goal, Some(3));
+
+        /* The popup starts with the current goal, emptying it removes the goal */
+        press(&mut app, KeyCode::Char('g'));
+        assert_eq!(app.popup, Some(Popup::Goal("3".to_string())));
+        press(&mut app, KeyCode::Backspace);
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.counter().goal, None);
+    }
+
+    #[test]
+    fn reaching_the_goal_is_stored_and_notified() {
+        let mut app = App { clock: || 1_000, ..Default::default() };
+        app.set_goal("2").unwrap();
+        press(&mut app, KeyCode::Right);
+        assert_eq!(app.counter().reached_at, None);
+        assert!(!app.bell);
+
+        app.clock = || 1_060;
+        press(&mut app, KeyCode::Right);
+        assert_eq!(app.counter().reached_at, Some(1_060));
+        assert!(app.bell);
+        assert_eq!(app.flash_until, Some(1_062));
+
+        /* Going past the goal keeps the first time it was reached */
+        app.bell = false;
+        app.clock = || 1_100;
+        press(&mut app, KeyCode::Right);
+        assert_eq!(app.counter().reached_at, Some(1_060));
+        assert!(!app.bell);
+
+        /* Dropping below forgets it, reaching it again notifies again */
+        press(&mut app, KeyCode::Char('u'));
+        press(&mut app, KeyCode::Char('u'));
+        assert_eq!(app.counter().reached_at, None);
+        press(&mut app, KeyCode::Right);
+        assert_eq!(app.counter().reached_at, Some(1_100));
+        assert!(app.bell);
+    }
+
+    #[test]
+    fn render_goal_gauge() {
+        let mut app = App { clock: || 1_000, ..Default::default() };
+        app.set_goal("8").unwrap();
+        (0..2).for_each(|_| press(&mut app, KeyCode::Right));
+        let mut buf = Buffer::empty(Rect::new(0, 0, 60, 6));
+        app.render(buf.area, &mut buf);
+
+        assert_eq!(line(&buf, 2), "┃           │                   Value: 2                   ┃");
+        assert_eq!(line(&buf, 3), "┃           │2/8 ━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━┃");
+        assert_eq!(buf[(20, 3)].fg, Color::Cyan);
+        assert_eq!(buf[(40, 3)].fg, Color::DarkGray);
+        assert_eq!(line(&buf, 4), "┃           │                Changed 0s ago                ┃");
+
+        (0..6).for_each(|_| press(&mut app, KeyCode::Right));
+        let mut buf = Buffer::empty(Rect::new(0, 0, 60, 6));
+        app.render(buf.area, &mut buf);
+
+        assert!(line(&buf, 3).starts_with("┃           │Goal 8 reached at "));
+        assert_eq!(buf[(50, 3)].fg, Color::Green);
+        /* The border flashes until FLASH_SECONDS have passed */
+        assert_eq!(buf[(0, 0)].fg, Color::Green);
+        app.clock = || 1_002;
+        let mut buf = Buffer::empty(Rect::new(0, 0, 60, 6));
+        app.render(buf.area, &mut buf);
+        assert_eq!(buf[(0, 0)].fg, Color::Reset);
+    }
+}
@@ -0,0 +1,190 @@
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+
+/* A line sent to the control socket, the counter name is optional and defaults to the selected one */
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Increment(Option<String>), /* inc [name] */
+    Decrement(Option<String>), /* dec [name] */
+    Set(i64, Option<String>), /* set N [name] */
+    Get(Option<String>) /* get [name] */
+}
+
+impl Command {
+    /// # Parse one line of the protocol
+    /// Everything after the command (and the number for `set`) is the counter name
+    pub fn parse(line: &str) -> Result<Command, String> {
+        let line = line.trim();
+        let (word, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let name = |text: &str| Some(text.trim().to_string()).filter(|name| !name.is_empty());
+        match word {
+            "inc" => Ok(Command::Increment(name(rest))),
+            "dec" => Ok(Command::Decrement(name(rest))),
+            "get" => Ok(Command::Get(name(rest))),
+            "set" => {
+                let rest = rest.trim_start();
+                let (number, rest) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+                let value = number.parse().map_err(|_| format!("set expects a whole number, got '{number}'"))?;
+                Ok(Command::Set(value, name(rest)))
+            }
+            "" => Err("empty command".to_string()),
+            _ => Err(format!("unknown command '{word}', use inc, dec, set N or get")),
+        }
+    }
+}
+
+/* A command waiting for the app, the reply is the line written back to the client */
+#[derive(Debug)]
+pub struct Request {
+    pub command: Command,
+    pub reply: Sender<String>
+}
+
+/* Listens on a Unix domain socket until dropped, the socket file is removed then */
+#[derive(Debug)]
+pub struct ControlSocket {
+    path: PathBuf
+}
+
+impl ControlSocket {
+
+    /// # Start listening
+    /// Every connection gets its own thread, commands are sent to `events` one at a time and
+    /// each waits for its reply. A socket file left behind by a crashed run is replaced, one
+    /// another instance is still listening on is not
+    pub fn bind<E>(path: PathBuf, events: Sender<E>) -> io::Result<ControlSocket>
+    where
+        E: From<Request> + Send + 'static,
+    {
+        let listener = match UnixListener::bind(&path) {
+            Err(err) if err.kind() == io::ErrorKind::AddrInUse && UnixStream::connect(&path).is_err() => {
+                fs::remove_file(&path)?;
+                UnixListener::bind(&path)?
+            }
+            Err(err) if err.kind() == io::ErrorKind::AddrInUse => {
+                return Err(io::Error::new(err.kind(), format!("{} is in use by another counter app", path.display())));
+            }
+            result => result?,
+        };
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let events = events.clone();
+                thread::spawn(move || serve(stream, events));
+            }
+        });
+        Ok(ControlSocket { path })
+    }
+}
+
+impl Drop for ControlSocket {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/* Answers the lines of one client until it hangs up or the app is gone */
+fn serve<E: From<Request>>(stream: UnixStream, events: Sender<E>) {
+    let Ok(mut writer) = stream.try_clone() else { return };
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else { return };
+        let reply = match Command::parse(&line) {
+            Ok(command) => {
+                let (reply, answer) = mpsc::channel();
+                if events.send(E::from(Request { command, reply })).is_err() {
+                    return;
+                }
+                match answer.recv() {
+                    Ok(reply) => reply,
+                    Err(_) => return,
+                }
+            }
+            Err(err) => format!("error {err}"),
+        };
+        if writeln!(writer, "{reply}").is_err() {
+            return;
+        }
+    }
+}
+
+/*
+   -----
+   TESTS
+   -----
+ */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    fn socket_path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("counter-app-{name}-{}.sock", std::process::id()))
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(Command::parse("inc"), Ok(Command::Increment(None)));
+        assert_eq!(Command::parse(" dec  code reviews \n"), Ok(Command::Decrement(Some("code reviews".to_string()))));
+        assert_eq!(Command::parse("set -5"), Ok(Command::Set(-5, None)));
+        assert_eq!(Command::parse("set 12 commits"), Ok(Command::Set(12, Some("commits".to_string()))));
+        assert_eq!(Command::parse("get commits"), Ok(Command::Get(Some("commits".to_string()))));
+
+        assert_eq!(Command::parse(""), Err("empty command".to_string()));
+        assert_eq!(Command::parse("set x"), Err("set expects a whole number, got 'x'".to_string()));
+        assert_eq!(Command::parse("set"), Err("set expects a whole number, got ''".to_string()));
+        assert!(Command::parse("reset").unwrap_err().starts_with("unknown command 'reset'"));
+    }
+
+    #[test]
+    fn replies_come_from_the_receiver() {
+        let path = socket_path("replies");
+        let (sender, requests) = mpsc::channel::<Request>();
+        let socket = ControlSocket::bind(path.clone(), sender).unwrap();
+
+        /* Stands in for the app: counts and answers with the value */
+        thread::spawn(move || {
+            let mut value = 0;
+            for request in requests {
+                match request.command {
+                    Command::Increment(_) => value += 1,
+                    Command::Decrement(_) => value -= 1,
+                    Command::Set(new, _) => value = new,
+                    Command::Get(_) => {}
+                }
+                request.reply.send(format!("ok {value}")).unwrap();
+            }
+        });
+
+        let mut client = UnixStream::connect(&path).unwrap();
+        client.write_all(b"inc\ninc\ndec\nbogus\nset 40\nget\n").unwrap();
+        let replies: Vec<String> = BufReader::new(client).lines().take(6).map(Result::unwrap).collect();
+
+        assert_eq!(replies[..3], ["ok 1", "ok 2", "ok 1"]);
+        assert!(replies[3].starts_with("error unknown command 'bogus'"));
+        assert_eq!(replies[4..], ["ok 40", "ok 40"]);
+
+        drop(socket);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn stale_socket_files_are_replaced() {
+        let path = socket_path("stale");
+        let _ = fs::remove_file(&path);
+        /* Bound and dropped without cleanup, like after a crash */
+        drop(UnixListener::bind(&path).unwrap());
+        assert!(path.exists());
+
+        let (sender, _requests) = mpsc::channel::<Request>();
+        let socket = ControlSocket::bind(path.clone(), sender.clone()).unwrap();
+
+        /* A second instance on the same path is refused */
+        let err = ControlSocket::bind(path.clone(), sender).unwrap_err();
+        assert!(err.to_string().contains("in use by another counter app"));
+        drop(socket);
+    }
+}
@@ -0,0 +1,153 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::counter::Counter;
+
+/* Everything that survives a restart */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedState {
+    pub counters: Vec<Counter>,
+    pub selected: usize
+}
+
+/* The JSON file the state is kept in */
+#[derive(Debug)]
+pub struct StateFile {
+    path: PathBuf
+}
+
+impl StateFile {
+    pub fn new(path: PathBuf) -> StateFile {
+        StateFile { path }
+    }
+
+    /// # Default location
+    /// `counters.json` in the user's data directory, e.g. `~/.local/share/ratatui-counter-app/`
+    /// on Linux. Falls back to the working directory if there is no data directory
+    pub fn default_path() -> PathBuf {
+        dirs::data_dir()
+            .map(|dir| dir.join(env!("CARGO_PKG_NAME")))
+            .unwrap_or_default()
+            .join("counters.json")
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// # Load the saved state
+    /// The error is a warning for the user, the caller is expected to go on with defaults.
+    /// A corrupt file is moved aside to `<file>.corrupt`, so the next save does not destroy it
+    pub fn load(&self) -> Result<SavedState, String> {
+        let text = match fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                return Err(format!("No saved counters in {}, starting fresh", self.path.display()));
+            }
+            Err(err) => return Err(format!("Could not read {}: {err}", self.path.display())),
+        };
+        let state: SavedState = serde_json::from_str(&text)
+            .map_err(|err| self.set_aside(&format!("is corrupt ({err})")))?;
+        if state.counters.is_empty() {
+            return Err(self.set_aside("has no counters"));
+        }
+        Ok(state)
+    }
+
+    /// # Save the state
+    /// Written to a temporary file first and renamed, so a crash never leaves half a file
+    pub fn save(&self, state: &SavedState) -> io::Result<()> {
+        if let Some(dir) = self.path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        let temp = self.path.with_extension("json.tmp");
+        fs::write(&temp, serde_json::to_string_pretty(state)?)?;
+        fs::rename(&temp, &self.path)
+    }
+
+    /* Moves an unusable file out of the way and builds the warning about it */
+    fn set_aside(&self, problem: &str) -> String {
+        let backup = self.path.with_extension("json.corrupt");
+        match fs::rename(&self.path, &backup) {
+            Ok(()) => format!(
+                "{} {problem}, moved to {}, starting fresh", self.path.display(), backup.display()
+            ),
+            Err(_) => format!("{} {problem}, starting fresh", self.path.display()),
+        }
+    }
+}
+
+/*
+   -----
+   TESTS
+   -----
+ */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    /* Fresh directory per test, removed at the end of the test */
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("counter-app-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn state() -> SavedState {
+        SavedState {
+            counters: vec![Counter::new("reviews", 3), Counter::new("incidents", 1)],
+            selected: 1,
+        }
+    }
+
+    #[test]
+    fn save_and_load() {
+        let dir = temp_dir("save");
+        let file = StateFile::new(dir.join("nested").join("counters.json"));
+
+        file.save(&state()).unwrap();
+        assert_eq!(file.load(), Ok(state()));
+        assert!(!dir.join("nested").join("counters.json.tmp").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn missing_file_is_a_warning() {
+        let file = StateFile::new(temp_dir("missing").join("counters.json"));
+
+        let warning = file.load().unwrap_err();
+        assert!(warning.starts_with("No saved counters in "), "{warning}");
+    }
+
+    #[test]
+    fn corrupt_file_is_moved_aside() {
+        let dir = temp_dir("corrupt");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("counters.json");
+        fs::write(&path, "{ not json").unwrap();
+        let file = StateFile::new(path.clone());
+
+        let warning = file.load().unwrap_err();
+        assert!(warning.contains("is corrupt"), "{warning}");
+        assert!(!path.exists());
+        assert_eq!(fs::read_to_string(dir.join("counters.json.corrupt")).unwrap(), "{ not json");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn empty_counter_list_is_rejected() {
+        let dir = temp_dir("empty");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("counters.json");
+        fs::write(&path, r#"{"counters": [], "selected": 0}"#).unwrap();
+
+        let warning = StateFile::new(path).load().unwrap_err();
+        assert!(warning.contains("has no counters"), "{warning}");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
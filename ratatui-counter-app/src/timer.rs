@@ -0,0 +1,139 @@
+use chrono::{Local, TimeZone};
+
+/* Seconds in a day, wall-clock times repeat with this period */
+const DAY: u64 = 24 * 60 * 60;
+
+/// # Parse a duration like `90s`, `10m`, `2h` or `1d`
+/// A plain number is taken as seconds, zero is refused
+pub fn parse_duration(text: &str) -> Result<u64, String> {
+    let (number, unit) = match text.find(|c: char| !c.is_ascii_digit()) {
+        Some(index) => text.split_at(index),
+        None => (text, "s"),
+    };
+    let multiplier = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => DAY,
+        _ => return Err(format!("unknown duration '{text}', use e.g. 90s, 10m, 2h or 1d")),
+    };
+    match number.parse::<u64>() {
+        Ok(0) => Err("duration has to be longer than 0".to_string()),
+        Ok(amount) => amount
+            .checked_mul(multiplier)
+            .ok_or_else(|| format!("duration '{text}' is too long")),
+        Err(_) => Err(format!("unknown duration '{text}', use e.g. 90s, 10m, 2h or 1d")),
+    }
+}
+
+/// # Parse a time of day like `06:30`
+/// Returns the seconds since midnight
+pub fn parse_clock_time(text: &str) -> Result<u64, String> {
+    let invalid = || format!("unknown time '{text}', use HH:MM like 06:30");
+    let (hours, minutes) = text.split_once(':').ok_or_else(invalid)?;
+    let hours: u64 = hours.parse().map_err(|_| invalid())?;
+    let minutes: u64 = minutes.parse().map_err(|_| invalid())?;
+    if hours > 23 || minutes > 59 {
+        return Err(invalid());
+    }
+    Ok(hours * 60 * 60 + minutes * 60)
+}
+
+/// # Short human readable duration
+/// `45s`, `3m 12s`, `2h 05m` or `3d 4h`, only the two largest units are shown
+pub fn format_duration(seconds: u64) -> String {
+    let (days, hours, minutes) = (seconds / DAY, seconds % DAY / 3600, seconds % 3600 / 60);
+    if days > 0 {
+        format!("{days}d {hours}h")
+    } else if hours > 0 {
+        format!("{hours}h {minutes:02}m")
+    } else if minutes > 0 {
+        format!("{minutes}m {:02}s", seconds % 60)
+    } else {
+        format!("{seconds}s")
+    }
+}
+
+/// # Did the clock pass a time of day
+/// True if `time_of_day` (seconds after local midnight) falls in `(from, to]`.
+/// `from` and `to` are Unix times, `offset` is the local offset from UTC in seconds
+pub fn passed_time_of_day(time_of_day: u64, offset: i64, from: u64, to: u64) -> bool {
+    if to <= from {
+        return false;
+    }
+    /* Local time of `from`, shifted so the time of day is at the start of a day */
+    let local = (from as i64 + offset - time_of_day as i64).rem_euclid(DAY as i64) as u64;
+    let until_next = DAY - local;
+    until_next <= to - from
+}
+
+/// # Offset of the local time zone from UTC, in seconds
+/// Daylight saving time is taken into account at the given Unix time, an unknown time is taken as UTC
+pub fn local_offset(at: u64) -> i64 {
+    i64::try_from(at).ok()
+        .and_then(|at| Local.timestamp_opt(at, 0).single())
+        .map_or(0, |time| time.offset().local_minus_utc() as i64)
+}
+
+/*
+   -----
+   TESTS
+   -----
+ */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn durations() {
+        assert_eq!(parse_duration("90s"), Ok(90));
+        assert_eq!(parse_duration("90"), Ok(90));
+        assert_eq!(parse_duration("10m"), Ok(600));
+        assert_eq!(parse_duration("2h"), Ok(7_200));
+        assert_eq!(parse_duration("1d"), Ok(86_400));
+        assert!(parse_duration("0m").is_err());
+        assert!(parse_duration("m").is_err());
+        assert!(parse_duration("5 minutes").is_err());
+        assert!(parse_duration("99999999999999999999d").is_err());
+    }
+
+    #[test]
+    fn clock_times() {
+        assert_eq!(parse_clock_time("00:00"), Ok(0));
+        assert_eq!(parse_clock_time("06:30"), Ok(23_400));
+        assert_eq!(parse_clock_time("23:59"), Ok(86_340));
+        assert!(parse_clock_time("24:00").is_err());
+        assert!(parse_clock_time("12:60").is_err());
+        assert!(parse_clock_time("noon").is_err());
+    }
+
+    #[test]
+    fn formatting() {
+        assert_eq!(format_duration(0), "0s");
+        assert_eq!(format_duration(45), "45s");
+        assert_eq!(format_duration(192), "3m 12s");
+        assert_eq!(format_duration(7_500), "2h 05m");
+        assert_eq!(format_duration(273_600), "3d 4h");
+    }
+
+    #[test]
+    fn passing_a_time_of_day() {
+        let six = 6 * 3600;
+        let day = 19_000 * DAY; /* some midnight UTC */
+
+        assert!(passed_time_of_day(six, 0, day + six - 1, day + six));
+        assert!(!passed_time_of_day(six, 0, day + six, day + six + 1));
+        assert!(!passed_time_of_day(six, 0, day, day + six - 1));
+        /* A long sleep still counts */
+        assert!(passed_time_of_day(six, 0, day + six + 1, day + DAY + six + 10));
+        assert!(!passed_time_of_day(six, 0, day + 10, day + 10));
+
+        /* 06:00 at UTC+2 is 04:00 UTC */
+        let two_hours = 2 * 3600;
+        assert!(passed_time_of_day(six, two_hours, day + 4 * 3600 - 1, day + 4 * 3600));
+        assert!(!passed_time_of_day(six, two_hours, day + six - 1, day + six));
+        /* 06:00 at UTC-8 is 14:00 UTC */
+        assert!(passed_time_of_day(six, -4 * two_hours, day + 14 * 3600 - 1, day + 14 * 3600));
+    }
+}
@@ -1,9 +1,21 @@
-use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use indexmap::IndexMap;
+
+use crate::import::{self, ImportFormat};
+use crate::templates::{self, Template};
 
 /* Enum to store Application state (What the user is seeing right now in this case) */
 pub enum CurrentScreen {
     Main,
     Editing,
+    Renaming,
+    ConfirmRename, /* The new key already exists, asking before overwriting it */
+    Templates, /* Choosing a template to start a new document from */
+    ConfirmTemplate, /* There are pairs already, asking before the template replaces them */
+    Import, /* Asking for a file to import, or for pasted text */
+    ImportPreview, /* Showing the parsed pairs before merging them */
     Exiting
 }
 /* When we are editing this enum stores if we are editing the Key or the Value */
@@ -17,18 +29,32 @@ pub enum CurrentlyEditing {
 pub struct App {
     pub key_input: String, /* the currently being edited json key */
     pub value_input: String, /* the currently being edited json value */
-    pub pairs: HashMap<String, String>, /* Representation of out key and value pairs */
+    pub pairs: IndexMap<String, String>, /* Representation of out key and value pairs, in insertion order */
+    pub selected: Option<usize>, /* Index of the selected row in the list of pairs */
     pub current_screen: CurrentScreen,
-    pub currently_editing: Option<CurrentlyEditing> /* Optional state containing key / value editing info */
+    pub currently_editing: Option<CurrentlyEditing>, /* Optional state containing key / value editing info */
+    pub templates_dir: PathBuf, /* Directory the templates are loaded from */
+    pub templates: Vec<Template>, /* Templates found when the template screen was opened */
+    pub template_selected: Option<usize>, /* Index of the previewed template */
+    pub import_path: String, /* the currently being edited path of the file to import */
+    pub import_error: Option<String>, /* Why the last import attempt failed */
+    pub imported: Vec<(String, String)> /* Parsed pairs waiting for confirmation */
 }
 impl App {
     pub fn new() -> App {
         App {
             key_input: String::new(),
             value_input: String::new(),
-            pairs: HashMap::new(),
+            pairs: IndexMap::new(),
+            selected: None,
             current_screen: CurrentScreen::Main,
             currently_editing: None,
+            templates_dir: PathBuf::from(templates::DEFAULT_TEMPLATES_DIR),
+            templates: Vec::new(),
+            template_selected: None,
+            import_path: String::new(),
+            import_error: None,
+            imported: Vec::new(),
         }
     }
 
@@ -51,9 +77,356 @@ impl App {
             self.currently_editing = Some(CurrentlyEditing::Key);
         }
     }
+    pub fn select_next(&mut self) {
+        self.selected = step_selection(self.selected, self.pairs.len(), true);
+    }
+    pub fn select_previous(&mut self) {
+        self.selected = step_selection(self.selected, self.pairs.len(), false);
+    }
+    /* Opens the Editing popup on the selected pair, ready to change its value */
+    pub fn start_edit_selected(&mut self) {
+        if let Some((key, value)) = self.selected.and_then(|i| self.pairs.get_index(i)) {
+            self.key_input = key.clone();
+            self.value_input = value.clone();
+            self.current_screen = CurrentScreen::Editing;
+            self.currently_editing = Some(CurrentlyEditing::Value);
+        }
+    }
+    /* Key of the selected row, if there is one */
+    pub fn selected_key(&self) -> Option<&String> {
+        self.selected
+            .and_then(|i| self.pairs.get_index(i))
+            .map(|(key, _)| key)
+    }
+    /* Opens the rename popup with the selected key as the starting text */
+    pub fn start_rename(&mut self) {
+        if let Some(key) = self.selected_key() {
+            self.key_input = key.clone();
+            self.current_screen = CurrentScreen::Renaming;
+        }
+    }
+    /* Renames the selected key to key_input, keeping its value and position */
+    /* Returns false without changing anything if the new key is used by another pair and overwrite is not set */
+    pub fn rename_selected(&mut self, overwrite: bool) -> bool {
+        let Some(mut index) = self.selected.filter(|i| *i < self.pairs.len()) else {
+            return true;
+        };
+        if let Some(existing) = self.pairs.get_index_of(&self.key_input)
+            && existing != index
+        {
+            if !overwrite {
+                return false;
+            }
+            /* The other pair goes away, the renamed one keeps its own value */
+            self.pairs.shift_remove_index(existing);
+            if existing < index {
+                index -= 1;
+            }
+        }
+        let (_, value) = self.pairs.shift_remove_index(index).unwrap();
+        self.pairs.shift_insert(index, std::mem::take(&mut self.key_input), value);
+        self.selected = Some(index);
+        true
+    }
+    /* (Re)loads the templates directory and shows the template screen */
+    /* A missing directory just means there is nothing to choose from */
+    pub fn open_templates(&mut self) {
+        self.templates = templates::load_templates(&self.templates_dir).unwrap_or_default();
+        self.template_selected = if self.templates.is_empty() { None } else { Some(0) };
+        self.current_screen = CurrentScreen::Templates;
+    }
+    pub fn select_next_template(&mut self) {
+        self.template_selected = step_selection(self.template_selected, self.templates.len(), true);
+    }
+    pub fn select_previous_template(&mut self) {
+        self.template_selected = step_selection(self.template_selected, self.templates.len(), false);
+    }
+    /* Template under the cursor on the template screen */
+    pub fn current_template(&self) -> Option<&Template> {
+        self.template_selected.and_then(|i| self.templates.get(i))
+    }
+    /* Replaces the pairs with the selected template and goes back to the Main screen */
+    /* Nothing happens if there is no usable template selected */
+    /* Returns false without changing anything if there are pairs already and replace is not set */
+    pub fn apply_selected_template(&mut self, replace: bool) -> bool {
+        if let Some(Ok(pairs)) = self.current_template().map(|t| t.pairs.clone()) {
+            if !replace && !self.pairs.is_empty() {
+                return false;
+            }
+            self.pairs = pairs;
+            self.selected = if self.pairs.is_empty() { None } else { Some(0) };
+            self.current_screen = CurrentScreen::Main;
+        }
+        true
+    }
+    /* Number of values still holding a template placeholder */
+    pub fn placeholders_left(&self) -> usize {
+        self.pairs.values().filter(|value| templates::is_placeholder(value)).count()
+    }
+    /* Shows the import popup with an empty path */
+    pub fn start_import(&mut self) {
+        self.import_path = String::new();
+        self.import_error = None;
+        self.imported = Vec::new();
+        self.current_screen = CurrentScreen::Import;
+    }
+    /* Reads the file at import_path, the format comes from its name or its content */
+    pub fn import_from_path(&mut self) {
+        let path = Path::new(self.import_path.trim());
+        match fs::read_to_string(path) {
+            Ok(text) => {
+                let format = import::format_for_path(path).unwrap_or_else(|| import::detect_format(&text));
+                self.preview_import(&text, format);
+            }
+            Err(err) => self.import_error = Some(format!("{}: {err}", path.display())),
+        }
+    }
+    /* Pasted text has no name, so the format is guessed from the content */
+    pub fn import_pasted(&mut self, text: &str) {
+        self.preview_import(text, import::detect_format(text));
+    }
+    /* Parses the text and moves on to the preview, or stays with an error */
+    fn preview_import(&mut self, text: &str, format: ImportFormat) {
+        match import::parse(text, format) {
+            Ok(pairs) if pairs.is_empty() => self.import_error = Some("Nothing to import".to_string()),
+            Ok(pairs) => {
+                self.imported = pairs;
+                self.import_error = None;
+                self.current_screen = CurrentScreen::ImportPreview;
+            }
+            Err(err) => self.import_error = Some(err),
+        }
+    }
+    /* Number of imported pairs that would change the value of an existing key */
+    pub fn import_conflicts(&self) -> usize {
+        self.imported
+            .iter()
+            .filter(|(key, value)| self.pairs.get(key).is_some_and(|old| old != value))
+            .count()
+    }
+    /* Adds the imported pairs and goes back to the Main screen */
+    /* Existing keys keep their place, their value is only replaced with overwrite set */
+    pub fn merge_import(&mut self, overwrite: bool) {
+        for (key, value) in std::mem::take(&mut self.imported) {
+            if overwrite || !self.pairs.contains_key(&key) {
+                self.pairs.insert(key, value);
+            }
+        }
+        self.current_screen = CurrentScreen::Main;
+    }
     pub fn print_json(&self) -> serde_json::Result<()> {
         let output = serde_json::to_string(&self.pairs)?;
         println!("{output}");
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+/* Moves a list selection by one row, staying inside 0..len */
+fn step_selection(selected: Option<usize>, len: usize, forward: bool) -> Option<usize> {
+    if len == 0 {
+        return None;
+    }
+    match selected {
+        Some(i) if forward => Some((i + 1).min(len - 1)),
+        Some(i) => Some(i.saturating_sub(1)),
+        None => Some(0),
+    }
+}
+
+/*
+   -----
+   TESTS
+   -----
+ */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app_with_pairs(pairs: &[(&str, &str)]) -> App {
+        let mut app = App::new();
+        for (key, value) in pairs {
+            app.pairs.insert(key.to_string(), value.to_string());
+        }
+        app
+    }
+
+    fn pairs(app: &App) -> Vec<(&str, &str)> {
+        app.pairs.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect()
+    }
+
+    #[test]
+    fn selection_stays_in_range() {
+        let mut app = app_with_pairs(&[("a", "1"), ("b", "2")]);
+
+        app.select_previous();
+        assert_eq!(app.selected, Some(0));
+        app.select_next();
+        app.select_next();
+        assert_eq!(app.selected, Some(1));
+        app.select_previous();
+        app.select_previous();
+        assert_eq!(app.selected, Some(0));
+
+        let mut empty = App::new();
+        empty.select_next();
+        assert_eq!(empty.selected, None);
+    }
+
+    #[test]
+    fn rename_keeps_value_and_position() {
+        let mut app = app_with_pairs(&[("a", "1"), ("b", "2"), ("c", "3")]);
+        app.selected = Some(1);
+        app.start_rename();
+        assert_eq!(app.key_input, "b");
+        app.key_input = "renamed".to_string();
+
+        assert!(app.rename_selected(false));
+        assert_eq!(pairs(&app), [("a", "1"), ("renamed", "2"), ("c", "3")]);
+        assert_eq!(app.selected, Some(1));
+        assert!(app.key_input.is_empty());
+    }
+
+    #[test]
+    fn rename_to_same_key_is_a_no_op() {
+        let mut app = app_with_pairs(&[("a", "1"), ("b", "2")]);
+        app.selected = Some(0);
+        app.start_rename();
+
+        assert!(app.rename_selected(false));
+        assert_eq!(pairs(&app), [("a", "1"), ("b", "2")]);
+    }
+
+    #[test]
+    fn rename_onto_existing_key_needs_overwrite() {
+        let mut app = app_with_pairs(&[("a", "1"), ("b", "2"), ("c", "3")]);
+        app.selected = Some(0);
+        app.key_input = "c".to_string();
+
+        assert!(!app.rename_selected(false));
+        assert_eq!(pairs(&app), [("a", "1"), ("b", "2"), ("c", "3")]);
+        assert_eq!(app.key_input, "c");
+
+        /* Overwriting drops the other pair, the renamed one keeps its value and place */
+        assert!(app.rename_selected(true));
+        assert_eq!(pairs(&app), [("c", "1"), ("b", "2")]);
+        assert_eq!(app.selected, Some(0));
+    }
+
+    #[test]
+    fn overwrite_before_the_selected_row_shifts_selection() {
+        let mut app = app_with_pairs(&[("a", "1"), ("b", "2"), ("c", "3")]);
+        app.selected = Some(2);
+        app.key_input = "a".to_string();
+
+        assert!(app.rename_selected(true));
+        assert_eq!(pairs(&app), [("b", "2"), ("a", "3")]);
+        assert_eq!(app.selected, Some(1));
+    }
+
+    #[test]
+    fn apply_template_replaces_pairs() {
+        let mut app = app_with_pairs(&[("old", "1")]);
+        app.templates = vec![Template {
+            name: "service".to_string(),
+            pairs: templates::parse_template(r#"{"name": "{{name}}", "port": "{{port}}", "log": "info"}"#),
+        }];
+        app.template_selected = Some(0);
+        app.current_screen = CurrentScreen::Templates;
+
+        /* The pair already entered is only replaced once that is confirmed */
+        assert!(!app.apply_selected_template(false));
+        assert!(matches!(app.current_screen, CurrentScreen::Templates));
+        assert_eq!(pairs(&app), [("old", "1")]);
+        assert!(app.apply_selected_template(true));
+        assert!(matches!(app.current_screen, CurrentScreen::Main));
+        assert_eq!(pairs(&app), [("name", "{{name}}"), ("port", "{{port}}"), ("log", "info")]);
+        assert_eq!(app.selected, Some(0));
+        assert_eq!(app.placeholders_left(), 2);
+
+        /* Filling in a value through the Editing popup clears its placeholder */
+        app.start_edit_selected();
+        assert_eq!(app.key_input, "name");
+        assert!(matches!(app.currently_editing, Some(CurrentlyEditing::Value)));
+        app.value_input = "billing".to_string();
+        app.save_key_value();
+        assert_eq!(pairs(&app)[0], ("name", "billing"));
+        assert_eq!(app.placeholders_left(), 1);
+    }
+
+    #[test]
+    fn broken_template_is_not_applied() {
+        let mut app = app_with_pairs(&[("old", "1")]);
+        app.templates = vec![Template { name: "broken".to_string(), pairs: Err("bad".to_string()) }];
+        app.template_selected = Some(0);
+        app.current_screen = CurrentScreen::Templates;
+
+        assert!(app.apply_selected_template(true));
+        assert!(matches!(app.current_screen, CurrentScreen::Templates));
+        assert_eq!(pairs(&app), [("old", "1")]);
+    }
+
+    #[test]
+    fn missing_templates_dir_opens_empty() {
+        let mut app = App::new();
+        app.templates_dir = PathBuf::from("does/not/exist");
+
+        app.open_templates();
+        assert!(matches!(app.current_screen, CurrentScreen::Templates));
+        assert!(app.templates.is_empty());
+        assert_eq!(app.template_selected, None);
+        app.select_next_template();
+        assert_eq!(app.template_selected, None);
+    }
+
+    #[test]
+    fn import_merge_overwrites_or_keeps() {
+        let mut app = app_with_pairs(&[("a", "1"), ("b", "2")]);
+        app.start_import();
+        app.import_pasted("b=20\nc=3\na=1");
+        assert!(matches!(app.current_screen, CurrentScreen::ImportPreview));
+        assert_eq!(app.import_conflicts(), 1);
+
+        app.merge_import(false);
+        assert!(matches!(app.current_screen, CurrentScreen::Main));
+        assert_eq!(pairs(&app), [("a", "1"), ("b", "2"), ("c", "3")]);
+        assert!(app.imported.is_empty());
+
+        app.start_import();
+        app.import_pasted("key,value\nb,20\nd,4");
+        app.merge_import(true);
+        assert_eq!(pairs(&app), [("a", "1"), ("b", "20"), ("c", "3"), ("d", "4")]);
+    }
+
+    #[test]
+    fn import_errors_stay_on_the_import_screen() {
+        let mut app = App::new();
+        app.start_import();
+
+        app.import_pasted("a=1\nno separator");
+        assert!(matches!(app.current_screen, CurrentScreen::Import));
+        assert_eq!(app.import_error.as_deref(), Some("line 2: missing '='"));
+
+        app.import_pasted("# only a comment");
+        assert_eq!(app.import_error.as_deref(), Some("Nothing to import"));
+
+        app.import_path = "does/not/exist.env".to_string();
+        app.import_from_path();
+        assert!(app.import_error.as_deref().unwrap().starts_with("does/not/exist.env: "));
+        assert!(app.imported.is_empty());
+    }
+
+    #[test]
+    fn import_from_csv_file() {
+        let path = std::env::temp_dir().join(format!("json-editor-import-{}.csv", std::process::id()));
+        /* The .csv name wins over the '=' in the content */
+        fs::write(&path, "url,https://example.com/?a=b\n").unwrap();
+        let mut app = App::new();
+        app.start_import();
+        app.import_path = path.display().to_string();
+
+        app.import_from_path();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(app.imported, [("url".to_string(), "https://example.com/?a=b".to_string())]);
+    }
+}
@@ -0,0 +1,148 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::time::Instant;
+
+use ratatui::crossterm::event::{self, Event};
+use serde::{Deserialize, Serialize};
+
+/* Anything that can hand events to run_app */
+/* Returning an error stops the application loop */
+pub trait EventSource {
+    fn next_event(&mut self) -> io::Result<Event>;
+}
+
+/* Real user input, blocking read from crossterm */
+pub struct CrosstermEvents;
+
+impl EventSource for CrosstermEvents {
+    fn next_event(&mut self) -> io::Result<Event> {
+        event::read()
+    }
+}
+
+/* Pre-made list of events, used by the tests and for replaying recorded sessions */
+/* When the list runs out an UnexpectedEof error is returned */
+pub struct ScriptedEvents {
+    events: VecDeque<Event>,
+}
+
+impl ScriptedEvents {
+    pub fn new(events: Vec<Event>) -> ScriptedEvents {
+        ScriptedEvents { events: events.into() }
+    }
+
+    /// # Load a recorded session
+    /// Reads a JSON Lines file written by the Recorder, the timestamps are dropped
+    pub fn from_recording(path: &Path) -> io::Result<ScriptedEvents> {
+        let mut events = Vec::new();
+        for line in BufReader::new(File::open(path)?).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let recorded: RecordedEvent = serde_json::from_str(&line)?;
+            events.push(recorded.event);
+        }
+        Ok(ScriptedEvents::new(events))
+    }
+}
+
+impl EventSource for ScriptedEvents {
+    fn next_event(&mut self) -> io::Result<Event> {
+        self.events
+            .pop_front()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "no more scripted events"))
+    }
+}
+
+/* One line of a recording file */
+#[derive(Serialize, Deserialize)]
+pub struct RecordedEvent {
+    pub elapsed_ms: u128, /* time since the start of the recording */
+    pub event: Event,
+}
+
+/* Wraps another EventSource and writes every event it returns to a file */
+/* Every event is flushed right away, so the file is usable even if the app crashes */
+pub struct Recorder<S: EventSource> {
+    source: S,
+    output: BufWriter<File>,
+    started: Instant,
+}
+
+impl<S: EventSource> Recorder<S> {
+    pub fn new(source: S, path: &Path) -> io::Result<Recorder<S>> {
+        Ok(Recorder {
+            source,
+            output: BufWriter::new(File::create(path)?),
+            started: Instant::now(),
+        })
+    }
+}
+
+impl<S: EventSource> EventSource for Recorder<S> {
+    fn next_event(&mut self) -> io::Result<Event> {
+        let event = self.source.next_event()?;
+        let recorded = RecordedEvent {
+            elapsed_ms: self.started.elapsed().as_millis(),
+            event,
+        };
+        serde_json::to_writer(&mut self.output, &recorded)?;
+        self.output.write_all(b"\n")?;
+        self.output.flush()?;
+        Ok(recorded.event)
+    }
+}
+
+/*
+   -----
+   TESTS
+   -----
+ */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, fs};
+    use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    fn press(c: char) -> Event {
+        Event::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn scripted_events_run_out() {
+        let mut source = ScriptedEvents::new(vec![press('a'), press('b')]);
+
+        assert_eq!(source.next_event().unwrap(), press('a'));
+        assert_eq!(source.next_event().unwrap(), press('b'));
+        let err = source.next_event().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn recording_round_trip() {
+        let path = env::temp_dir().join(format!("json-editor-recording-{}.jsonl", std::process::id()));
+        let events = vec![press('e'), Event::Resize(80, 24), press('q')];
+
+        let mut recorder = Recorder::new(ScriptedEvents::new(events.clone()), &path).unwrap();
+        for expected in &events {
+            assert_eq!(&recorder.next_event().unwrap(), expected);
+        }
+        assert!(recorder.next_event().is_err());
+        drop(recorder);
+
+        let written = fs::read_to_string(&path).unwrap();
+        assert_eq!(written.lines().count(), events.len());
+        assert!(written.lines().all(|line| line.contains("\"elapsed_ms\"")));
+
+        let mut replay = ScriptedEvents::from_recording(&path).unwrap();
+        for expected in &events {
+            assert_eq!(&replay.next_event().unwrap(), expected);
+        }
+        assert!(replay.next_event().is_err());
+        fs::remove_file(&path).unwrap();
+    }
+}
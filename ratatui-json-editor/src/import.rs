@@ -0,0 +1,216 @@
+use std::path::Path;
+
+/* Text formats the import understands */
+#[derive(Debug, PartialEq)]
+pub enum ImportFormat {
+    KeyValue, /* key=value lines, including .env files */
+    Csv /* two columns: key, value */
+}
+
+/// # Format of a file from its extension
+/// Returns None if the extension does not tell, the content has to be checked then
+pub fn format_for_path(path: &Path) -> Option<ImportFormat> {
+    let name = path.file_name()?.to_str()?.to_ascii_lowercase();
+    if name.ends_with(".csv") {
+        Some(ImportFormat::Csv)
+    } else if name.starts_with(".env") || name.ends_with(".env") {
+        Some(ImportFormat::KeyValue)
+    } else {
+        None
+    }
+}
+
+/// # Guess the format of pasted text
+/// The first meaningful line decides: an `=` before any `,` means key=value lines
+pub fn detect_format(text: &str) -> ImportFormat {
+    let first = text
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'));
+    match first {
+        Some(line) => match (line.find('='), line.find(',')) {
+            (Some(eq), Some(comma)) if eq < comma => ImportFormat::KeyValue,
+            (Some(_), None) => ImportFormat::KeyValue,
+            _ => ImportFormat::Csv,
+        },
+        None => ImportFormat::KeyValue,
+    }
+}
+
+/// # Parse text into key-value pairs
+/// Pairs keep the order of the text, the error message names the offending line
+pub fn parse(text: &str, format: ImportFormat) -> Result<Vec<(String, String)>, String> {
+    match format {
+        ImportFormat::KeyValue => parse_key_values(text),
+        ImportFormat::Csv => parse_csv(text),
+    }
+}
+
+/* key=value lines with the usual .env extras: comments, `export` and quoted values */
+fn parse_key_values(text: &str) -> Result<Vec<(String, String)>, String> {
+    let mut pairs = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(format!("line {}: missing '='", number + 1));
+        };
+        let key = key.trim();
+        if key.is_empty() {
+            return Err(format!("line {}: empty key", number + 1));
+        }
+        pairs.push((key.to_string(), unquote(value.trim())));
+    }
+    Ok(pairs)
+}
+
+/* Removes .env style quotes, unquoted values lose their trailing ` # comment` */
+fn unquote(value: &str) -> String {
+    if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+        let mut out = String::new();
+        let mut chars = value[1..value.len() - 1].chars();
+        while let Some(c) = chars.next() {
+            match (c, chars.clone().next()) {
+                ('\\', Some('n')) => { out.push('\n'); chars.next(); }
+                ('\\', Some(escaped @ ('"' | '\\'))) => { out.push(escaped); chars.next(); }
+                _ => out.push(c),
+            }
+        }
+        out
+    } else if value.len() >= 2 && value.starts_with('\'') && value.ends_with('\'') {
+        value[1..value.len() - 1].to_string()
+    } else {
+        match value.find(" #") {
+            Some(comment) => value[..comment].trim_end().to_string(),
+            None => value.to_string(),
+        }
+    }
+}
+
+/* Two column CSV, quoted fields may hold commas, quotes ("") and new lines */
+/* A first row of "key,value" is treated as a header and skipped */
+fn parse_csv(text: &str) -> Result<Vec<(String, String)>, String> {
+    let mut pairs = Vec::new();
+    for (line, record) in csv_records(text)? {
+        if record.len() == 1 && record[0].trim().is_empty() {
+            continue; /* blank line */
+        }
+        let [key, value] = <[String; 2]>::try_from(record)
+            .map_err(|record| format!("line {line}: expected 2 columns, found {}", record.len()))?;
+        let key = key.trim().to_string();
+        if pairs.is_empty() && key.eq_ignore_ascii_case("key") && value.trim().eq_ignore_ascii_case("value") {
+            continue;
+        }
+        if key.is_empty() {
+            return Err(format!("line {line}: empty key"));
+        }
+        pairs.push((key, value.trim().to_string()));
+    }
+    Ok(pairs)
+}
+
+/* Splits CSV text into records, each with the line number it starts on */
+fn csv_records(text: &str) -> Result<Vec<(usize, Vec<String>)>, String> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut record_line = 1;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => { field.push('"'); chars.next(); }
+            '"' if in_quotes => in_quotes = false,
+            '"' if field.trim().is_empty() => { field.clear(); in_quotes = true; }
+            ',' if !in_quotes => record.push(std::mem::take(&mut field)),
+            '\r' if !in_quotes => {}
+            '\n' if !in_quotes => {
+                record.push(std::mem::take(&mut field));
+                records.push((record_line, std::mem::take(&mut record)));
+                line += 1;
+                record_line = line;
+            }
+            '\n' => { field.push(c); line += 1; }
+            _ => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err(format!("line {record_line}: unclosed quote"));
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push((record_line, record));
+    }
+    Ok(records)
+}
+
+/*
+   -----
+   TESTS
+   -----
+ */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(list: &[(&str, &str)]) -> Vec<(String, String)> {
+        list.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn dotenv() {
+        let text = "# database\nexport DB_HOST=localhost\nDB_PORT = 5432 # default\n\nDB_PASS=\"p#ss \\\"x\\\"\"\nNAME='single quoted'\nEMPTY=\n";
+
+        assert_eq!(parse(text, ImportFormat::KeyValue).unwrap(), pairs(&[
+            ("DB_HOST", "localhost"),
+            ("DB_PORT", "5432"),
+            ("DB_PASS", "p#ss \"x\""),
+            ("NAME", "single quoted"),
+            ("EMPTY", ""),
+        ]));
+    }
+
+    #[test]
+    fn key_value_errors_name_the_line() {
+        assert_eq!(parse("a=1\njust text", ImportFormat::KeyValue).unwrap_err(), "line 2: missing '='");
+        assert_eq!(parse("=1", ImportFormat::KeyValue).unwrap_err(), "line 1: empty key");
+    }
+
+    #[test]
+    fn csv() {
+        let text = "key,value\nname,ratatui\n\"greeting\",\"hello, \"\"world\"\"\"\nmulti,\"two\nlines\"\r\nempty,\n";
+
+        assert_eq!(parse(text, ImportFormat::Csv).unwrap(), pairs(&[
+            ("name", "ratatui"),
+            ("greeting", "hello, \"world\""),
+            ("multi", "two\nlines"),
+            ("empty", ""),
+        ]));
+    }
+
+    #[test]
+    fn csv_errors_name_the_line() {
+        assert_eq!(parse("a,1\nb,2,3", ImportFormat::Csv).unwrap_err(), "line 2: expected 2 columns, found 3");
+        assert_eq!(parse("a,1\nb,\"open", ImportFormat::Csv).unwrap_err(), "line 2: unclosed quote");
+        assert_eq!(parse("a,\"x\ny\"\nc", ImportFormat::Csv).unwrap_err(), "line 3: expected 2 columns, found 1");
+    }
+
+    #[test]
+    fn formats() {
+        assert_eq!(format_for_path(Path::new("export.CSV")), Some(ImportFormat::Csv));
+        assert_eq!(format_for_path(Path::new("prod.env")), Some(ImportFormat::KeyValue));
+        assert_eq!(format_for_path(Path::new(".env")), Some(ImportFormat::KeyValue));
+        assert_eq!(format_for_path(Path::new("config/.env.local")), Some(ImportFormat::KeyValue));
+        assert_eq!(format_for_path(Path::new("pairs.txt")), None);
+
+        assert_eq!(detect_format("# comment\nA=1,2"), ImportFormat::KeyValue);
+        assert_eq!(detect_format("a,b=c"), ImportFormat::Csv);
+        assert_eq!(detect_format("a,b"), ImportFormat::Csv);
+    }
+}
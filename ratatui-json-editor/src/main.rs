@@ -1,9 +1,12 @@
-use std::{error::Error, io, time::Duration};
+use std::{env, error::Error, io, path::Path};
 
 use ratatui::{
-    backend::{Backend, CrosstermBackend},
+    backend::{Backend, CrosstermBackend, TestBackend},
     crossterm::{
-        event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
+        event::{
+            self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+            Event, KeyCode, KeyEventKind
+        },
         execute,
         terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
         
@@ -12,13 +15,42 @@ use ratatui::{
 };
 
 mod app;
+mod events;
+mod import;
+mod templates;
 mod ui;
 use crate::{
     app::{App, CurrentScreen, CurrentlyEditing},
+    events::{CrosstermEvents, EventSource, Recorder, ScriptedEvents},
     ui::ui
 };
 
+/* Size of the TestBackend used when replaying a recorded session */
+const REPLAY_WIDTH: u16 = 80;
+const REPLAY_HEIGHT: u16 = 24;
+
 fn main() -> Result<(), Box<dyn Error>> {
+    /*
+    Command line options
+    --record <file> : save every event of the session to <file>
+    --replay <file> : run a recorded session on a TestBackend and print the last frame
+    --templates <dir> : directory of the JSON templates (default: ./templates)
+    */
+    let args: Vec<String> = env::args().skip(1).collect();
+    let mut app = App::new();
+    if let Some(dir) = flag_value(&args, "--templates") {
+        app.templates_dir = dir.into();
+    }
+    if let Some(path) = flag_value(&args, "--replay") {
+        return replay_session(Path::new(path), app);
+    }
+
+    /* Prepare the event source before touching the terminal, so a bad path can not leave raw mode on */
+    let mut events: Box<dyn EventSource> = match flag_value(&args, "--record") {
+        Some(path) => Box::new(Recorder::new(CrosstermEvents, Path::new(path))?),
+        None => Box::new(CrosstermEvents),
+    };
+
     /*
     Setup terminal
     Here we are setting up stderr for rendering and stdout for printing.
@@ -27,23 +59,24 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     enable_raw_mode()?;
     let mut stderr = io::stderr(); // standard error
-    execute!(stderr, EnterAlternateScreen, EnableMouseCapture)?; // set up crossterm to stderr
+    execute!(stderr, EnterAlternateScreen, EnableMouseCapture, EnableBracketedPaste)?; // set up crossterm to stderr
+    /* Bracketed paste delivers pasted text as a single Event::Paste, used by the import */
 
 
     /* Using the prepared custom terminal as the backand for ratatui */
     let backend = CrosstermBackend::new(stderr);
     let mut terminal= Terminal::new(backend)?;
 
-    /* Create app and run it  */
-    let mut app = App::new();
-    let res = run_app(&mut terminal, &mut app);
+    /* Run the app */
+    let res = run_app(&mut terminal, &mut app, events.as_mut());
 
     /* Restore the terminal to the state we were starting with */
     disable_raw_mode()?;
     execute!(
         terminal.backend_mut(),
         LeaveAlternateScreen,
-        DisableMouseCapture
+        DisableMouseCapture,
+        DisableBracketedPaste
     )?;
     terminal.show_cursor()?;
 
@@ -62,41 +95,174 @@ fn main() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
-//TODO
-fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::Result<bool> {
+/* Returns the value following a command line flag, if the flag is present */
+fn flag_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|arg| arg == flag)
+        .and_then(|i| args.get(i + 1))
+        .map(String::as_str)
+}
+
+/* Headless replay of a recording, useful to reproduce bugs from a saved session */
+fn replay_session(path: &Path, mut app: App) -> Result<(), Box<dyn Error>> {
+    let mut terminal = Terminal::new(TestBackend::new(REPLAY_WIDTH, REPLAY_HEIGHT))?;
+    let res = replay(path, &mut terminal, &mut app);
+
+    /* The last drawn frame is the interesting part of a replay */
+    println!("{}", terminal.backend());
+    match res {
+        Ok(true) => app.print_json()?,
+        Ok(false) => {}
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => {
+            println!("Recording ended before the application exited");
+        }
+        Err(err) => return Err(err.into()),
+    }
+    Ok(())
+}
+
+/* Feeds a recorded session into run_app */
+fn replay(path: &Path, terminal: &mut Terminal<TestBackend>, app: &mut App) -> io::Result<bool> {
+    let mut events = ScriptedEvents::from_recording(path)?;
+    run_app(terminal, app, &mut events)
+}
+
+/* Main loop of the application, draws the UI and hands every event to handle_event */
+/* Events are pulled from an EventSource, so tests and replays can stand in for the user */
+fn run_app<B: Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    events: &mut dyn EventSource
+) -> io::Result<bool> {
     loop {
         // Draw the UI
         terminal.draw(|f| ui(f, app))?;
 
         // Handling interaction
-        // if event::polyy(Duration:form_millis(250))? { ... } // To prevent blocking of read
-        if let Event::Key(key) = event::read()? { //read is bocking! TODO
-            if key.kind == event::KeyEventKind::Release {
-                continue; // Skip events that are not KeyEventKind::Press
-            }
-            match app.current_screen { // match for different screens
-                CurrentScreen::Main => match key.code {
-                    KeyCode::Char('e') => {
-                        app.current_screen = CurrentScreen::Editing;
-                        app.currently_editing = Some(CurrentlyEditing::Key);
-                    }
-                    KeyCode::Char('q') => {
-                        app.current_screen = CurrentScreen::Exiting;
-                    }
-                    _ => {}
-                },
-                CurrentScreen::Exiting => match key.code {
-                    KeyCode::Char('y') => {
-                        return Ok(true);
+        if let Some(do_print) = handle_event(app, events.next_event()?) {
+            return Ok(do_print);
+        }
+    }
+}
+
+/* Applies a single event to the app */
+/* Returns Some(do_print) when the user decided to leave the application */
+fn handle_event(app: &mut App, event: Event) -> Option<bool> {
+    /* Pasted text is only used by the import popup */
+    if let Event::Paste(text) = &event {
+        if let CurrentScreen::Import = app.current_screen {
+            app.import_pasted(text);
+        }
+        return None;
+    }
+    if let Event::Key(key) = event {
+        if key.kind == event::KeyEventKind::Release {
+            return None; // Skip events that are not KeyEventKind::Press
+        }
+        match app.current_screen { // match for different screens
+            CurrentScreen::Main => match key.code {
+                KeyCode::Char('e') => {
+                    app.current_screen = CurrentScreen::Editing;
+                    app.currently_editing = Some(CurrentlyEditing::Key);
+                }
+                KeyCode::Char('q') => {
+                    app.current_screen = CurrentScreen::Exiting;
+                }
+                KeyCode::Down => app.select_next(),
+                KeyCode::Up => app.select_previous(),
+                KeyCode::Char('r') => app.start_rename(),
+                KeyCode::Enter => app.start_edit_selected(),
+                KeyCode::Char('t') => app.open_templates(),
+                KeyCode::Char('i') => app.start_import(),
+                _ => {}
+            },
+            CurrentScreen::Import => match key.code {
+                KeyCode::Enter => app.import_from_path(),
+                KeyCode::Backspace => {
+                    app.import_path.pop();
+                }
+                KeyCode::Esc => {
+                    app.current_screen = CurrentScreen::Main;
+                }
+                KeyCode::Char(value) => {
+                    app.import_path.push(value);
+                }
+                _ => {}
+            },
+            CurrentScreen::ImportPreview => match key.code {
+                KeyCode::Char('y') | KeyCode::Enter => app.merge_import(true),
+                KeyCode::Char('k') => app.merge_import(false),
+                KeyCode::Char('n') | KeyCode::Esc => {
+                    app.imported = Vec::new();
+                    app.current_screen = CurrentScreen::Main;
+                }
+                _ => {}
+            },
+            CurrentScreen::Templates => match key.code {
+                KeyCode::Down => app.select_next_template(),
+                KeyCode::Up => app.select_previous_template(),
+                KeyCode::Enter => {
+                    let applied = app.apply_selected_template(false);
+                    if !applied { /* The pairs entered so far would be lost */
+                        app.current_screen = CurrentScreen::ConfirmTemplate;
                     }
-                    KeyCode::Char('n') | KeyCode::Char('q') => {
-                        return Ok(false);
+                }
+                KeyCode::Esc => {
+                    app.current_screen = CurrentScreen::Main;
+                }
+                _ => {}
+            },
+            CurrentScreen::ConfirmTemplate => match key.code {
+                KeyCode::Char('y') => {
+                    app.apply_selected_template(true);
+                }
+                KeyCode::Char('n') | KeyCode::Esc => {
+                    app.current_screen = CurrentScreen::Templates;
+                }
+                _ => {}
+            },
+            CurrentScreen::Renaming => match key.code {
+                KeyCode::Enter => {
+                    if app.rename_selected(false) {
+                        app.current_screen = CurrentScreen::Main;
+                    } else { /* Another pair already has this key */
+                        app.current_screen = CurrentScreen::ConfirmRename;
                     }
-                    _ => {}
-                },
-                CurrentScreen::Editing if key.kind == KeyEventKind::Press => {
-                    match key.code {
-                        KeyCode::Enter => {
+                }
+                KeyCode::Backspace => {
+                    app.key_input.pop();
+                }
+                KeyCode::Esc => {
+                    app.key_input = String::new();
+                    app.current_screen = CurrentScreen::Main;
+                }
+                KeyCode::Char(value) => {
+                    app.key_input.push(value);
+                }
+                _ => {}
+            },
+            CurrentScreen::ConfirmRename => match key.code {
+                KeyCode::Char('y') => {
+                    app.rename_selected(true);
+                    app.current_screen = CurrentScreen::Main;
+                }
+                KeyCode::Char('n') | KeyCode::Esc => {
+                    app.current_screen = CurrentScreen::Renaming;
+                }
+                _ => {}
+            },
+            CurrentScreen::Exiting => match key.code {
+                KeyCode::Char('y') => {
+                    return Some(true);
+                }
+                KeyCode::Char('n') | KeyCode::Char('q') => {
+                    return Some(false);
+                }
+                _ => {}
+            },
+            CurrentScreen::Editing if key.kind == KeyEventKind::Press => {
+                match key.code {
+                    KeyCode::Enter => {
                         if let Some(editing) = &app.currently_editing {
                             match editing {
                                 CurrentlyEditing::Key => {
@@ -105,47 +271,346 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::Result<
                                 CurrentlyEditing::Value => {
                                     app.save_key_value();
                                     app.current_screen = CurrentScreen::Main;
-                                    }
                                 }
                             }
                         }
-                        KeyCode::Backspace => {
-                            if let Some(editing) = &app.currently_editing {
-                                match editing {
-                                    CurrentlyEditing::Key => {
-                                        app.key_input.pop();
-                                    }
-                                    CurrentlyEditing::Value => {
-                                        app.value_input.pop();
-                                    }
+                    }
+                    KeyCode::Backspace => {
+                        if let Some(editing) = &app.currently_editing {
+                            match editing {
+                                CurrentlyEditing::Key => {
+                                    app.key_input.pop();
+                                }
+                                CurrentlyEditing::Value => {
+                                    app.value_input.pop();
                                 }
                             }
                         }
-                        KeyCode::Esc => {
-                            app.current_screen = CurrentScreen::Main;
-                            app.currently_editing = None;
-                        }
-                        KeyCode::Tab => {
-                            app.toggle_editing();
-                        }
-                        KeyCode::Char(value) => {
-                            if let Some(editing) = &app.currently_editing {
-                                match editing {
-                                    CurrentlyEditing::Key => {
-                                        app.key_input.push(value);
-                                    }
-                                    CurrentlyEditing::Value => {
-                                        app.value_input.push(value);
-                                    }
+                    }
+                    KeyCode::Esc => {
+                        app.current_screen = CurrentScreen::Main;
+                        app.currently_editing = None;
+                    }
+                    KeyCode::Tab => {
+                        app.toggle_editing();
+                    }
+                    KeyCode::Char(value) => {
+                        if let Some(editing) = &app.currently_editing {
+                            match editing {
+                                CurrentlyEditing::Key => {
+                                    app.key_input.push(value);
+                                }
+                                CurrentlyEditing::Value => {
+                                    app.value_input.push(value);
                                 }
                             }
                         }
-                        _ => {}
                     }
+                    _ => {}
                 }
-                _ => {}
             }
+            _ => {}
         }
- 
     }
-}
\ No newline at end of file
+    None
+}
+
+/*
+   -----
+   TESTS
+   -----
+ */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use ratatui::crossterm::event::{KeyEvent, KeyModifiers};
+
+    fn press(code: KeyCode) -> Event {
+        Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    fn release(code: KeyCode) -> Event {
+        Event::Key(KeyEvent::new_with_kind(code, KeyModifiers::NONE, KeyEventKind::Release))
+    }
+
+    /* Turns a text into key presses */
+    fn type_text(text: &str) -> Vec<Event> {
+        text.chars().map(|c| press(KeyCode::Char(c))).collect()
+    }
+
+    fn run(app: &mut App, events: Vec<Event>) -> io::Result<bool> {
+        let mut terminal = Terminal::new(TestBackend::new(60, 20)).unwrap();
+        run_app(&mut terminal, app, &mut ScriptedEvents::new(events))
+    }
+
+    #[test]
+    fn add_pair_and_print() {
+        let mut app = App::new();
+        let mut events = vec![press(KeyCode::Char('e'))];
+        events.extend(type_text("name"));
+        events.push(press(KeyCode::Enter));
+        events.extend(type_text("ratatui"));
+        events.push(press(KeyCode::Enter));
+        events.extend([press(KeyCode::Char('q')), press(KeyCode::Char('y'))]);
+
+        assert!(run(&mut app, events).unwrap());
+        assert_eq!(app.pairs.get("name").map(String::as_str), Some("ratatui"));
+        assert!(app.currently_editing.is_none());
+        assert!(app.key_input.is_empty() && app.value_input.is_empty());
+    }
+
+    #[test]
+    fn quit_without_print() {
+        let mut app = App::new();
+        assert!(!run(&mut app, vec![press(KeyCode::Char('q')), press(KeyCode::Char('n'))]).unwrap());
+
+        let mut app = App::new();
+        assert!(!run(&mut app, vec![press(KeyCode::Char('q')), press(KeyCode::Char('q'))]).unwrap());
+    }
+
+    #[test]
+    fn tab_and_backspace_edit_the_focused_box() {
+        let mut app = App::new();
+        let mut events = vec![press(KeyCode::Char('e'))];
+        events.extend(type_text("keyy"));
+        events.push(press(KeyCode::Backspace));
+        events.push(press(KeyCode::Tab));
+        events.extend(type_text("value"));
+        events.push(press(KeyCode::Tab));
+        events.push(press(KeyCode::Char('s')));
+
+        assert!(run(&mut app, events).is_err()); // script ran out while editing
+        assert!(matches!(app.current_screen, CurrentScreen::Editing));
+        assert!(matches!(app.currently_editing, Some(CurrentlyEditing::Key)));
+        assert_eq!(app.key_input, "keys");
+        assert_eq!(app.value_input, "value");
+    }
+
+    #[test]
+    fn esc_cancels_editing() {
+        let mut app = App::new();
+        let mut events = vec![press(KeyCode::Char('e'))];
+        events.extend(type_text("name"));
+        events.push(press(KeyCode::Esc));
+
+        assert!(run(&mut app, events).is_err());
+        assert!(matches!(app.current_screen, CurrentScreen::Main));
+        assert!(app.currently_editing.is_none());
+        assert!(app.pairs.is_empty());
+    }
+
+    #[test]
+    fn release_events_are_ignored() {
+        let mut app = App::new();
+        let events = vec![release(KeyCode::Char('e')), release(KeyCode::Char('q'))];
+
+        assert!(run(&mut app, events).is_err());
+        assert!(matches!(app.current_screen, CurrentScreen::Main));
+    }
+
+    #[test]
+    fn last_frame_shows_current_screen() {
+        let mut app = App::new();
+        let mut terminal = Terminal::new(TestBackend::new(60, 20)).unwrap();
+        let events = vec![press(KeyCode::Char('q'))];
+
+        assert!(run_app(&mut terminal, &mut app, &mut ScriptedEvents::new(events)).is_err());
+        let buf = terminal.backend().buffer();
+        let line: String = (0..buf.area.width).map(|x| buf[(x, 7)].symbol()).collect();
+        assert_eq!(line.trim(), "Y/N");
+    }
+
+    #[test]
+    fn recorded_session_replays_the_same() {
+        let path = std::env::temp_dir().join(format!("json-editor-session-{}.jsonl", std::process::id()));
+        let mut events = vec![press(KeyCode::Char('e'))];
+        events.extend(type_text("name"));
+        events.push(press(KeyCode::Tab));
+        events.extend(type_text("ratatui"));
+        events.push(press(KeyCode::Enter));
+        events.push(press(KeyCode::Char('q')));
+
+        /* Record the session while running it */
+        let mut app = App::new();
+        let mut terminal = Terminal::new(TestBackend::new(REPLAY_WIDTH, REPLAY_HEIGHT)).unwrap();
+        let mut recorder = Recorder::new(ScriptedEvents::new(events), &path).unwrap();
+        assert!(run_app(&mut terminal, &mut app, &mut recorder).is_err());
+        drop(recorder);
+
+        /* Replay it from the file */
+        let mut replayed_app = App::new();
+        let mut replayed_terminal = Terminal::new(TestBackend::new(REPLAY_WIDTH, REPLAY_HEIGHT)).unwrap();
+        let res = replay(&path, &mut replayed_terminal, &mut replayed_app);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(res.unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(replayed_app.pairs, app.pairs);
+        assert!(matches!(replayed_app.current_screen, CurrentScreen::Exiting));
+        assert_eq!(replayed_terminal.backend().buffer(), terminal.backend().buffer());
+    }
+
+    #[test]
+    fn flag_values() {
+        let args: Vec<String> = ["--record", "session.jsonl", "--replay"].map(String::from).into();
+
+        assert_eq!(flag_value(&args, "--record"), Some("session.jsonl"));
+        assert_eq!(flag_value(&args, "--replay"), None);
+        assert_eq!(flag_value(&args, "--other"), None);
+    }
+
+    #[test]
+    fn rename_selected_key() {
+        let mut app = App::new();
+        app.pairs.insert("name".to_string(), "ratatui".to_string());
+        app.pairs.insert("port".to_string(), "8080".to_string());
+        let mut events = vec![press(KeyCode::Down), press(KeyCode::Down), press(KeyCode::Char('r'))];
+        events.extend(std::iter::repeat_n(press(KeyCode::Backspace), 4));
+        events.extend(type_text("host"));
+        events.push(press(KeyCode::Enter));
+
+        assert!(run(&mut app, events).is_err());
+        assert!(matches!(app.current_screen, CurrentScreen::Main));
+        assert_eq!(app.pairs.get_index(1), Some((&"host".to_string(), &"8080".to_string())));
+        assert_eq!(app.pairs.len(), 2);
+    }
+
+    #[test]
+    fn rename_conflict_asks_for_confirmation() {
+        let mut app = App::new();
+        app.pairs.insert("name".to_string(), "ratatui".to_string());
+        app.pairs.insert("port".to_string(), "8080".to_string());
+        let mut events = vec![press(KeyCode::Down), press(KeyCode::Char('r'))];
+        events.extend(std::iter::repeat_n(press(KeyCode::Backspace), 4));
+        events.extend(type_text("port"));
+        events.push(press(KeyCode::Enter));
+
+        /* Refused first, the user is asked and goes back to editing with (n) */
+        let mut declined = events.clone();
+        declined.push(press(KeyCode::Char('n')));
+        assert!(run(&mut app, declined).is_err());
+        assert!(matches!(app.current_screen, CurrentScreen::Renaming));
+        assert_eq!(app.pairs.len(), 2);
+
+        /* Confirming overwrites the other pair */
+        let mut app = App::new();
+        app.pairs.insert("name".to_string(), "ratatui".to_string());
+        app.pairs.insert("port".to_string(), "8080".to_string());
+        events.push(press(KeyCode::Char('y')));
+        assert!(run(&mut app, events).is_err());
+        assert!(matches!(app.current_screen, CurrentScreen::Main));
+        assert_eq!(app.pairs.len(), 1);
+        assert_eq!(app.pairs.get("port").map(String::as_str), Some("ratatui"));
+    }
+
+    #[test]
+    fn new_from_bundled_template() {
+        let mut app = App::new();
+        app.templates_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("templates");
+        /* Bundled templates are sorted: credentials, feature-flags, service-manifest */
+        let events = vec![
+            press(KeyCode::Char('t')),
+            press(KeyCode::Down),
+            press(KeyCode::Down),
+            press(KeyCode::Enter),
+        ];
+
+        assert!(run(&mut app, events).is_err());
+        assert!(matches!(app.current_screen, CurrentScreen::Main));
+        assert_eq!(app.pairs.get_index(0).map(|(k, _)| k.as_str()), Some("name"));
+        assert!(app.placeholders_left() > 0);
+    }
+
+    #[test]
+    fn enter_applies_and_esc_cancels_templates() {
+        let mut app = App::new();
+        app.pairs.insert("name".to_string(), "ratatui".to_string());
+        app.templates_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("templates");
+        let events = vec![press(KeyCode::Char('t')), press(KeyCode::Enter), press(KeyCode::Char('y')), press(KeyCode::Char('q'))];
+
+        /* Enter applied the first template once replacing the pair was confirmed, (q) from Main asks to exit */
+        assert!(run(&mut app, events).is_err());
+        assert!(matches!(app.current_screen, CurrentScreen::Exiting));
+        assert!(!app.pairs.contains_key("name"));
+
+        let mut app = App::new();
+        app.pairs.insert("name".to_string(), "ratatui".to_string());
+        let events = vec![press(KeyCode::Char('t')), press(KeyCode::Esc)];
+        assert!(run(&mut app, events).is_err());
+        assert!(matches!(app.current_screen, CurrentScreen::Main));
+        assert_eq!(app.pairs.len(), 1);
+    }
+
+    #[test]
+    fn declined_template_keeps_the_pairs() {
+        let mut app = App::new();
+        app.pairs.insert("name".to_string(), "ratatui".to_string());
+        app.templates_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("templates");
+        let events = vec![press(KeyCode::Char('t')), press(KeyCode::Enter)];
+
+        /* Asked first, (n) goes back to choosing a template */
+        let mut declined = events.clone();
+        declined.push(press(KeyCode::Char('n')));
+        assert!(run(&mut app, declined).is_err());
+        assert!(matches!(app.current_screen, CurrentScreen::Templates));
+        assert_eq!(app.pairs.len(), 1);
+        assert_eq!(app.pairs.get("name").map(String::as_str), Some("ratatui"));
+
+        let mut app = App::new();
+        app.templates_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("templates");
+        app.pairs.insert("name".to_string(), "ratatui".to_string());
+        let mut escaped = events;
+        escaped.push(press(KeyCode::Esc));
+        assert!(run(&mut app, escaped).is_err());
+        assert!(matches!(app.current_screen, CurrentScreen::Templates));
+        assert_eq!(app.pairs.len(), 1);
+    }
+
+    #[test]
+    fn paste_import_and_merge() {
+        let mut app = App::new();
+        app.pairs.insert("name".to_string(), "ratatui".to_string());
+        let events = vec![
+            press(KeyCode::Char('i')),
+            Event::Paste("name=tui\nport=8080".to_string()),
+            press(KeyCode::Char('k')),
+        ];
+
+        assert!(run(&mut app, events).is_err());
+        assert!(matches!(app.current_screen, CurrentScreen::Main));
+        assert_eq!(app.pairs.get("name").map(String::as_str), Some("ratatui"));
+        assert_eq!(app.pairs.get("port").map(String::as_str), Some("8080"));
+    }
+
+    #[test]
+    fn paste_outside_import_is_ignored() {
+        let mut app = App::new();
+        let events = vec![Event::Paste("name=tui".to_string())];
+
+        assert!(run(&mut app, events).is_err());
+        assert!(app.pairs.is_empty());
+    }
+
+    #[test]
+    fn import_typed_path_then_cancel_preview() {
+        let path = std::env::temp_dir().join(format!("json-editor-typed-{}.env", std::process::id()));
+        fs::write(&path, "export A=1\n").unwrap();
+        let mut events = vec![press(KeyCode::Char('i'))];
+        events.extend(type_text(&path.display().to_string()));
+        events.push(press(KeyCode::Enter));
+
+        let mut app = App::new();
+        let mut preview = events.clone();
+        preview.push(press(KeyCode::Esc));
+        assert!(run(&mut app, preview).is_err());
+        assert!(matches!(app.current_screen, CurrentScreen::Main));
+        assert!(app.pairs.is_empty());
+
+        let mut app = App::new();
+        events.push(press(KeyCode::Enter));
+        assert!(run(&mut app, events).is_err());
+        fs::remove_file(&path).unwrap();
+        assert_eq!(app.pairs.get("A").map(String::as_str), Some("1"));
+    }
+}
@@ -0,0 +1,125 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use indexmap::IndexMap;
+use serde_json::Value;
+
+/* Default place to look for templates, relative to the working directory */
+pub const DEFAULT_TEMPLATES_DIR: &str = "templates";
+
+/* A JSON document the user can start from */
+/* pairs holds the parse error instead when the file is not a flat JSON object */
+pub struct Template {
+    pub name: String, /* file name without the .json extension */
+    pub pairs: Result<IndexMap<String, String>, String>,
+}
+
+/// # Load every template of a directory
+/// Reads all `*.json` files of `dir`, sorted by name. Files that can not be parsed
+/// are still listed, so the user can see what is wrong with them
+pub fn load_templates(dir: &Path) -> io::Result<Vec<Template>> {
+    let mut paths: Vec<_> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+
+    Ok(paths
+        .iter()
+        .map(|path| Template {
+            name: path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            pairs: fs::read_to_string(path)
+                .map_err(|err| err.to_string())
+                .and_then(|text| parse_template(&text)),
+        })
+        .collect())
+}
+
+/// # Parse the content of a template file
+/// The document has to be a JSON object, non string values are kept as their JSON text
+pub fn parse_template(text: &str) -> Result<IndexMap<String, String>, String> {
+    /* Reading into an IndexMap keeps the key order of the file */
+    let object: IndexMap<String, Value> = serde_json::from_str(text).map_err(|err| err.to_string())?;
+    Ok(object
+        .into_iter()
+        .map(|(key, value)| match value {
+            Value::String(text) => (key, text),
+            other => (key, other.to_string()),
+        })
+        .collect())
+}
+
+/// # Is this value still a placeholder
+/// Placeholders look like `{{service name}}` and are meant to be replaced by the user
+pub fn is_placeholder(value: &str) -> bool {
+    let value = value.trim();
+    value.len() >= 4 && value.starts_with("{{") && value.ends_with("}}")
+}
+
+/*
+   -----
+   TESTS
+   -----
+ */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn parse_keeps_order_and_stringifies_values() {
+        let pairs = parse_template(r#"{"name": "{{name}}", "port": 8080, "debug": false}"#).unwrap();
+
+        let pairs: Vec<_> = pairs.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+        assert_eq!(pairs, [("name", "{{name}}"), ("port", "8080"), ("debug", "false")]);
+    }
+
+    #[test]
+    fn parse_rejects_non_objects() {
+        assert!(parse_template("[1, 2]").is_err());
+        assert!(parse_template("{ not json").is_err());
+    }
+
+    #[test]
+    fn placeholders() {
+        assert!(is_placeholder("{{service name}}"));
+        assert!(is_placeholder(" {{x}} "));
+        assert!(!is_placeholder("{{}"));
+        assert!(!is_placeholder("api"));
+        assert!(!is_placeholder("{{half"));
+    }
+
+    #[test]
+    fn load_sorted_json_files_only() {
+        let dir = env::temp_dir().join(format!("json-editor-templates-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("b.json"), r#"{"b": "1"}"#).unwrap();
+        fs::write(dir.join("a.json"), r#"{"a": "{{a}}"}"#).unwrap();
+        fs::write(dir.join("broken.json"), "[]").unwrap();
+        fs::write(dir.join("notes.txt"), "not a template").unwrap();
+
+        let templates = load_templates(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let names: Vec<_> = templates.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["a", "b", "broken"]);
+        assert!(templates[0].pairs.is_ok());
+        assert!(templates[2].pairs.is_err());
+    }
+
+    #[test]
+    fn bundled_templates_are_valid() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join(DEFAULT_TEMPLATES_DIR);
+        let templates = load_templates(&dir).unwrap();
+
+        assert!(!templates.is_empty());
+        for template in templates {
+            assert!(template.pairs.is_ok(), "{} does not parse", template.name);
+        }
+    }
+}
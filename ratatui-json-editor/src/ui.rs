@@ -1,11 +1,13 @@
 use ratatui::style::{
     Style,
-    Color
+    Color,
+    Stylize
 };
 use ratatui::widgets::{
     Block, 
     Borders,
-    List, ListItem,
+    List, ListItem, ListState,
+    Row, Table,
     Paragraph, Wrap,
     Clear
 };
@@ -14,6 +16,7 @@ use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use ratatui::text::{Line, Span, Text};
 
 use crate::app::{App, CurrentScreen, CurrentlyEditing};
+use crate::templates;
 
 /*
 Widgets are constructed and then drawn onto the screen using a Frame, which is placed within a specified Rect
@@ -45,27 +48,43 @@ pub fn ui(frame: &mut Frame, app: &App) {
         .borders(Borders::ALL)
         .style(Style::default());
 
-    let title = Paragraph::new(Text::styled(
+    /* Template placeholders still waiting for a value are counted in the title */
+    let mut title_spans = vec![Span::styled(
         "Create New JSON",
         Style::default().fg(Color::Green) //Set foreground color
-    ))
+    )];
+    let placeholders_left = app.placeholders_left();
+    if placeholders_left > 0 {
+        title_spans.push(Span::styled(
+            format!(" - {placeholders_left} placeholder(s) to fill"),
+            placeholder_style()
+        ));
+    }
+    let title = Paragraph::new(Line::from(title_spans))
     .block(title_block); // Surrounding the paragraph by the title_block
     frame.render_widget(title, chunks[0]); // Rendering the widget to the first chunk (top)
 
     ///// Middle chunk /////
     
-    /* We would like to see the previous key-value pairs oon the gui */
-    let mut list_items = Vec::<ListItem>::new();
-    for key in app.pairs.keys() {
-        list_items.push(ListItem::new(Line::from(Span::styled(
-            format!("{: <25} : {}", key, app.pairs.get(key).unwrap()), //CONTENT
-            Style::default().fg(Color::Yellow)  //STYLE
-        ))));
+    if let CurrentScreen::Templates | CurrentScreen::ConfirmTemplate = app.current_screen {
+        /* The template browser takes the place of the pairs */
+        render_templates(frame, app, chunks[1]);
+    } else if let CurrentScreen::ImportPreview = app.current_screen {
+        /* So does the table of pairs waiting to be imported */
+        render_import_preview(frame, app, chunks[1]);
+    } else {
+        /* We would like to see the previous key-value pairs oon the gui */
+        let list_items: Vec<ListItem> = app.pairs.iter()
+            .map(|(key, value)| pair_item(key, value))
+            .collect();
+
+        /* The selected row is highlighted, the state only lives for this frame */
+        let list = List::from_iter(list_items)
+            .highlight_style(Style::default().bg(Color::Yellow).fg(Color::Black));
+        let mut list_state = ListState::default().with_selected(app.selected);
+        frame.render_stateful_widget(list, chunks[1], &mut list_state); // rendering the middle chunk
     }
 
-    let list = List::from_iter(list_items.into_iter()); //TODO tutorial does not works
-    frame.render_widget(list, chunks[1]); // rendering the middle chunk
-
 
     ///// Middle Chunk /////
     
@@ -79,6 +98,15 @@ pub fn ui(frame: &mut Frame, app: &App) {
             CurrentScreen::Editing => {
                 Span::styled("Editing Mode", Style::default().fg(Color::Yellow)) 
             }
+            CurrentScreen::Renaming | CurrentScreen::ConfirmRename => {
+                Span::styled("Renaming Mode", Style::default().fg(Color::Yellow))
+            }
+            CurrentScreen::Templates | CurrentScreen::ConfirmTemplate => {
+                Span::styled("Template Mode", Style::default().fg(Color::Yellow))
+            }
+            CurrentScreen::Import | CurrentScreen::ImportPreview => {
+                Span::styled("Import Mode", Style::default().fg(Color::Yellow))
+            }
             CurrentScreen::Exiting => {
                 Span::styled("Exiting", Style::default().fg(Color::LightRed))
             }
@@ -103,6 +131,14 @@ pub fn ui(frame: &mut Frame, app: &App) {
                     Span::styled("Editing JSON value", Style::default().fg(Color::LightGreen))
                 }
             }
+        } else if let CurrentScreen::Renaming | CurrentScreen::ConfirmRename = app.current_screen {
+            Span::styled("Renaming JSON key", Style::default().fg(Color::Green))
+        } else if let CurrentScreen::Templates | CurrentScreen::ConfirmTemplate = app.current_screen {
+            Span::styled("Choosing a template", Style::default().fg(Color::Green))
+        } else if let CurrentScreen::Import = app.current_screen {
+            Span::styled("Waiting for a file or paste", Style::default().fg(Color::Green))
+        } else if let CurrentScreen::ImportPreview = app.current_screen {
+            Span::styled("Reviewing the import", Style::default().fg(Color::Green))
         } else {
             Span::styled("Not Editing Anything", Style::default().fg(Color::DarkGray))
         }
@@ -116,13 +152,37 @@ pub fn ui(frame: &mut Frame, app: &App) {
     let current_keys_hint = {
         match app.current_screen {
             CurrentScreen::Main => Span::styled(
-                "(q) to quit / (e) to make new pair",
+                "(q) to quit / (e) to make new pair / (t) new from template / (i) to import / (UP/DOWN) to select / (ENTER) to edit value / (r) to rename key",
                 Style::default().fg(Color::Red)
             ),
             CurrentScreen::Editing => Span::styled(
                 "(ESC) to cancel / (TAB) to switch boxes / (ENTER) to complete",
                 Style::default().fg(Color::Red)
             ),
+            CurrentScreen::Renaming => Span::styled(
+                "(ESC) to cancel / (ENTER) to rename",
+                Style::default().fg(Color::Red)
+            ),
+            CurrentScreen::ConfirmRename => Span::styled(
+                "(y) to overwrite / (n) to change the key",
+                Style::default().fg(Color::Red)
+            ),
+            CurrentScreen::Templates => Span::styled(
+                "(ESC) to cancel / (UP/DOWN) to select / (ENTER) to use template",
+                Style::default().fg(Color::Red)
+            ),
+            CurrentScreen::ConfirmTemplate => Span::styled(
+                "(y) to replace the pairs / (n) to keep them",
+                Style::default().fg(Color::Red)
+            ),
+            CurrentScreen::Import => Span::styled(
+                "(ESC) to cancel / (ENTER) to read the file / or paste the text",
+                Style::default().fg(Color::Red)
+            ),
+            CurrentScreen::ImportPreview => Span::styled(
+                "(y) to merge, replacing conflicts / (k) to merge, keeping existing values / (ESC) to cancel",
+                Style::default().fg(Color::Red)
+            ),
             CurrentScreen::Exiting => Span::styled(
                 "(q) to quit / (e) to make new pair",
                 Style::default().fg(Color::Red)
@@ -135,7 +195,7 @@ pub fn ui(frame: &mut Frame, app: &App) {
 
     let footer_chunks = Layout::default()
         .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(50), Constraint::Percentage((50))])
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
         .split(chunks[2]); // Applying the Layout configuration to the bottom chunk
 
     frame.render_widget(mode_footer, footer_chunks[0]);
@@ -157,7 +217,7 @@ pub fn ui(frame: &mut Frame, app: &App) {
         let popup_chunks = Layout::default()
             .direction(Direction::Horizontal)
             .margin(1)
-            .constraints([Constraint::Percentage((50)), Constraint::Percentage((50))])
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
             .split(area);
 
         /* Creating Blocks with paragraps for key - value content */
@@ -181,6 +241,84 @@ pub fn ui(frame: &mut Frame, app: &App) {
         frame.render_widget(value_text, popup_chunks[1]);
     }
 
+    ///// Rename popup /////
+
+    if let CurrentScreen::Renaming | CurrentScreen::ConfirmRename = app.current_screen {
+        let title = match app.current_screen {
+            CurrentScreen::ConfirmRename => format!("\"{}\" exists, overwrite? (y/n)", app.key_input),
+            _ => format!("Rename \"{}\"", app.selected_key().map(String::as_str).unwrap_or_default()),
+        };
+        let popup_block = Block::default()
+            .title(title)
+            .borders(Borders::NONE)
+            .style(Style::default().bg(Color::DarkGray));
+        let area = centered_rect(60, 25, frame.area());
+        frame.render_widget(popup_block, area);
+
+        let popup_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .margin(1)
+            .constraints([Constraint::Percentage(100)])
+            .split(area);
+
+        /* Same highlight as the key box while editing, confirmation turns it red */
+        let key_block = Block::default().title("New key").borders(Borders::ALL);
+        let key_block = match app.current_screen {
+            CurrentScreen::ConfirmRename => key_block.style(Style::default().bg(Color::LightRed).fg(Color::Black)),
+            _ => key_block.style(Style::default().bg(Color::LightYellow).fg(Color::Black)),
+        };
+        let key_text = Paragraph::new(app.key_input.clone()).block(key_block);
+        frame.render_widget(key_text, popup_chunks[0]);
+    }
+
+    ///// Replace pairs popup /////
+
+    if let CurrentScreen::ConfirmTemplate = app.current_screen {
+        let popup_block = Block::default()
+            .title("Replace the pairs? (y/n)")
+            .borders(Borders::NONE)
+            .style(Style::default().bg(Color::DarkGray));
+        let name = app.current_template().map(|t| t.name.as_str()).unwrap_or_default();
+        let confirm_text = Text::styled(
+            format!("\"{name}\" replaces the {} pair(s) entered so far", app.pairs.len()),
+            Style::default().fg(Color::LightRed)
+        );
+        let confirm_paragraph = Paragraph::new(confirm_text)
+            .block(popup_block)
+            .wrap(Wrap{trim: false});
+
+        let area = centered_rect(60, 25, frame.area());
+        frame.render_widget(Clear, area);
+        frame.render_widget(confirm_paragraph, area);
+    }
+
+    ///// Import popup /////
+
+    if let CurrentScreen::Import = app.current_screen {
+        /* The last error takes the place of the title */
+        let popup_block = match &app.import_error {
+            Some(err) => Block::default().title(Span::styled(err.as_str(), Style::default().fg(Color::LightRed))),
+            None => Block::default().title("Import .env / key=value / CSV"),
+        }
+            .borders(Borders::NONE)
+            .style(Style::default().bg(Color::DarkGray));
+        let area = centered_rect(60, 25, frame.area());
+        frame.render_widget(popup_block, area);
+
+        let popup_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .margin(1)
+            .constraints([Constraint::Percentage(100)])
+            .split(area);
+
+        let path_block = Block::default()
+            .title("File path (or paste)")
+            .borders(Borders::ALL)
+            .style(Style::default().bg(Color::LightYellow).fg(Color::Black));
+        let path_text = Paragraph::new(app.import_path.clone()).block(path_block);
+        frame.render_widget(path_text, popup_chunks[0]);
+    }
+
     ///// Clear when exiting /////
 
     if let CurrentScreen::Exiting = app.current_screen {
@@ -209,6 +347,80 @@ pub fn ui(frame: &mut Frame, app: &App) {
 
     
 
+}
+
+/* Style of values that are still template placeholders */
+fn placeholder_style() -> Style {
+    Style::default().bg(Color::LightMagenta).fg(Color::Black)
+}
+
+/* One key-value row, placeholders are highlighted until they are filled in */
+fn pair_item<'a>(key: &str, value: &'a str) -> ListItem<'a> {
+    let value_style = if templates::is_placeholder(value) {
+        placeholder_style()
+    } else {
+        Style::default().fg(Color::Yellow)
+    };
+    ListItem::new(Line::from(vec![
+        Span::styled(format!("{: <25} : ", key), Style::default().fg(Color::Yellow)),
+        Span::styled(value, value_style)
+    ]))
+}
+
+/* Template screen: list of templates on the left, preview of the selected one on the right */
+fn render_templates(frame: &mut Frame, app: &App, area: Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(30), Constraint::Percentage(70)])
+        .split(area);
+
+    let names = List::from_iter(app.templates.iter().map(|t| ListItem::new(t.name.as_str())))
+        .block(Block::default().title("Templates").borders(Borders::ALL))
+        .highlight_style(Style::default().bg(Color::Yellow).fg(Color::Black));
+    let mut names_state = ListState::default().with_selected(app.template_selected);
+    frame.render_stateful_widget(names, chunks[0], &mut names_state);
+
+    let preview_block = Block::default().title("Preview").borders(Borders::ALL);
+    match app.current_template().map(|t| &t.pairs) {
+        Some(Ok(pairs)) => {
+            let preview = List::from_iter(pairs.iter().map(|(key, value)| pair_item(key, value)))
+                .block(preview_block);
+            frame.render_widget(preview, chunks[1]);
+        }
+        Some(Err(err)) => {
+            let error = Paragraph::new(Text::styled(format!("Invalid template: {err}"), Style::default().fg(Color::Red)))
+                .block(preview_block)
+                .wrap(Wrap { trim: false });
+            frame.render_widget(error, chunks[1]);
+        }
+        None => {
+            let empty = Paragraph::new(Text::styled(
+                format!("No templates in {}", app.templates_dir.display()),
+                Style::default().fg(Color::DarkGray)
+            ))
+                .block(preview_block)
+                .wrap(Wrap { trim: false });
+            frame.render_widget(empty, chunks[1]);
+        }
+    }
+}
+
+/* Import preview: every parsed pair with what merging it would do */
+fn render_import_preview(frame: &mut Frame, app: &App, area: Rect) {
+    let rows = app.imported.iter().map(|(key, value)| {
+        let (status, style) = match app.pairs.get(key) {
+            None => ("new".to_string(), Style::default().fg(Color::Green)),
+            Some(old) if old == value => ("unchanged".to_string(), Style::default().fg(Color::DarkGray)),
+            Some(old) => (format!("replaces: {old}"), Style::default().fg(Color::LightRed)),
+        };
+        Row::new([key.clone(), value.clone(), status]).style(style)
+    });
+
+    let title = format!("Import preview: {} pairs, {} conflicts", app.imported.len(), app.import_conflicts());
+    let table = Table::new(rows, [Constraint::Percentage(30), Constraint::Percentage(40), Constraint::Percentage(30)])
+        .header(Row::new(["Key", "Value", "Status"]).bold())
+        .block(Block::default().title(title).borders(Borders::ALL));
+    frame.render_widget(table, area);
 }
 
 /// helper function to create a centered rect using up certain
@@ -225,8 +437,9 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
         .split(r);
 
     // Cut the middle vertical piece into three width-wise pieces
+    // (Horizontal, cutting it vertically again made every popup full width and a line or two high)
     Layout::default()
-            .direction(Direction::Vertical)
+            .direction(Direction::Horizontal)
             .constraints([
                 Constraint::Percentage((100 - percent_x) / 2),
                 Constraint::Percentage(percent_x ),
@@ -234,3 +447,346 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
             ])
             .split(popup_layout[1])[1] // return the middle chunk
 }
+
+/*
+   -----
+   TESTS
+   -----
+ */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{backend::TestBackend, buffer::Buffer, Terminal};
+    use crate::templates::Template;
+
+    /* Renders the ui into a 60x20 TestBackend and returns the resulting buffer */
+    fn render(app: &App) -> Buffer {
+        let mut terminal = Terminal::new(TestBackend::new(60, 20)).unwrap();
+        terminal.draw(|f| ui(f, app)).unwrap();
+        terminal.backend().buffer().clone()
+    }
+
+    /* Collects the symbols of the buffer line by line, styles are checked separately */
+    fn lines(buf: &Buffer) -> Vec<String> {
+        (0..buf.area.height)
+            .map(|y| (0..buf.area.width).map(|x| buf[(x, y)].symbol()).collect())
+            .collect()
+    }
+
+    /* App with a single pair, so the HashMap order does not matter */
+    fn app_with_pair() -> App {
+        let mut app = App::new();
+        app.pairs.insert("name".to_string(), "ratatui".to_string());
+        app
+    }
+
+    fn app_editing(editing: CurrentlyEditing) -> App {
+        let mut app = app_with_pair();
+        app.current_screen = CurrentScreen::Editing;
+        app.currently_editing = Some(editing);
+        app.key_input = "port".to_string();
+        app.value_input = "8080".to_string();
+        app
+    }
+
+    const EDITING_LINES: [&str; 20] = [
+        "┌──────────────────────────────────────────────────────────┐",
+        "│Create New JSON                                           │",
+        "└──────────────────────────────────────────────────────────┘",
+        "name                      : ratatui                         ",
+        "                                                            ",
+        "                                                            ",
+        "                                                            ",
+        "            Enter a new key-value pair                      ",
+        "             ┌Key────────────┐┌Value──────────┐             ",
+        "             │port           ││8080           │             ",
+        "             └───────────────┘└───────────────┘             ",
+        "                                                            ",
+        "                                                            ",
+        "                                                            ",
+        "                                                            ",
+        "                                                            ",
+        "                                                            ",
+        "┌────────────────────────────┐┌────────────────────────────┐",
+        "│Editing Mode | Editing JSON ││(ESC) to cancel / (TAB) to s│",
+        "└────────────────────────────┘└────────────────────────────┘",
+    ];
+
+    #[test]
+    fn render_main() {
+        let buf = render(&app_with_pair());
+
+        assert_eq!(lines(&buf), [
+            "┌──────────────────────────────────────────────────────────┐",
+            "│Create New JSON                                           │",
+            "└──────────────────────────────────────────────────────────┘",
+            "name                      : ratatui                         ",
+            "                                                            ",
+            "                                                            ",
+            "                                                            ",
+            "                                                            ",
+            "                                                            ",
+            "                                                            ",
+            "                                                            ",
+            "                                                            ",
+            "                                                            ",
+            "                                                            ",
+            "                                                            ",
+            "                                                            ",
+            "                                                            ",
+            "┌────────────────────────────┐┌────────────────────────────┐",
+            "│Normal Mode | Not Editing An││(q) to quit / (e) to make ne│",
+            "└────────────────────────────┘└────────────────────────────┘",
+        ]);
+        assert_eq!(buf[(1, 1)].fg, Color::Green);
+        assert_eq!(buf[(0, 3)].fg, Color::Yellow);
+        assert_eq!(buf[(1, 18)].fg, Color::Green);
+        assert_eq!(buf[(31, 18)].fg, Color::Red);
+    }
+
+    #[test]
+    fn render_editing_key() {
+        let buf = render(&app_editing(CurrentlyEditing::Key));
+
+        assert_eq!(lines(&buf), EDITING_LINES);
+        assert_eq!(buf[(12, 7)].bg, Color::DarkGray);
+        /* Only the key box is highlighted */
+        assert_eq!(buf[(14, 9)].bg, Color::LightYellow);
+        assert_eq!(buf[(33, 9)].bg, Color::DarkGray);
+        assert_eq!(buf[(17, 18)].fg, Color::Green);
+    }
+
+    #[test]
+    fn render_editing_value() {
+        let buf = render(&app_editing(CurrentlyEditing::Value));
+
+        assert_eq!(lines(&buf), EDITING_LINES);
+        /* Only the value box is highlighted */
+        assert_eq!(buf[(14, 9)].bg, Color::DarkGray);
+        assert_eq!(buf[(33, 9)].bg, Color::LightYellow);
+        assert_eq!(buf[(17, 18)].fg, Color::LightGreen);
+    }
+
+    #[test]
+    fn render_exiting() {
+        let mut app = app_with_pair();
+        app.current_screen = CurrentScreen::Exiting;
+        let buf = render(&app);
+
+        assert_eq!(lines(&buf), [
+            "                                                            ",
+            "                                                            ",
+            "                                                            ",
+            "                                                            ",
+            "                                                            ",
+            "                                                            ",
+            "                                                            ",
+            "            Y/N                                             ",
+            "            Would you like to output the buffer             ",
+            "            as JSON? (y/n)                                  ",
+            "                                                            ",
+            "                                                            ",
+            "                                                            ",
+            "                                                            ",
+            "                                                            ",
+            "                                                            ",
+            "                                                            ",
+            "                                                            ",
+            "                                                            ",
+            "                                                            ",
+        ]);
+        assert_eq!(buf[(12, 8)].fg, Color::Red);
+        assert_eq!(buf[(12, 8)].bg, Color::DarkGray);
+    }
+
+    /* Two pairs with the second one being renamed to the first one's key */
+    fn app_renaming(screen: CurrentScreen) -> App {
+        let mut app = app_with_pair();
+        app.pairs.insert("port".to_string(), "8080".to_string());
+        app.selected = Some(1);
+        app.start_rename();
+        app.key_input = "name".to_string();
+        app.current_screen = screen;
+        app
+    }
+
+    #[test]
+    fn render_selected_row() {
+        let mut app = app_with_pair();
+        app.pairs.insert("port".to_string(), "8080".to_string());
+        app.selected = Some(1);
+        let buf = render(&app);
+
+        assert_eq!(lines(&buf)[3..5], [
+            "name                      : ratatui                         ",
+            "port                      : 8080                            ",
+        ]);
+        assert_eq!(buf[(0, 3)].bg, Color::Reset);
+        assert_eq!(buf[(0, 4)].bg, Color::Yellow);
+        assert_eq!(buf[(59, 4)].bg, Color::Yellow);
+    }
+
+    #[test]
+    fn render_renaming() {
+        let buf = render(&app_renaming(CurrentScreen::Renaming));
+
+        assert_eq!(lines(&buf)[7..11], [
+            "            Rename \"port\"                                   ",
+            "             ┌New key─────────────────────────┐             ",
+            "             │name                            │             ",
+            "             └────────────────────────────────┘             ",
+        ]);
+        assert_eq!(lines(&buf)[18], "│Renaming Mode | Renaming JSO││(ESC) to cancel / (ENTER) to│");
+        assert_eq!(buf[(14, 9)].bg, Color::LightYellow);
+    }
+
+    #[test]
+    fn render_confirm_rename() {
+        let buf = render(&app_renaming(CurrentScreen::ConfirmRename));
+
+        assert_eq!(lines(&buf)[7..11], [
+            "            \"name\" exists, overwrite? (y/n)                 ",
+            "             ┌New key─────────────────────────┐             ",
+            "             │name                            │             ",
+            "             └────────────────────────────────┘             ",
+        ]);
+        assert_eq!(lines(&buf)[18], "│Renaming Mode | Renaming JSO││(y) to overwrite / (n) to ch│");
+        assert_eq!(buf[(14, 9)].bg, Color::LightRed);
+    }
+
+    fn app_templates() -> App {
+        let mut app = App::new();
+        app.templates = vec![
+            Template {
+                name: "service".to_string(),
+                pairs: templates::parse_template(r#"{"name": "{{service name}}", "port": "8080"}"#),
+            },
+            Template { name: "broken".to_string(), pairs: Err("template is not a JSON object".to_string()) },
+        ];
+        app.template_selected = Some(0);
+        app.current_screen = CurrentScreen::Templates;
+        app
+    }
+
+    #[test]
+    fn render_templates_preview() {
+        let buf = render(&app_templates());
+
+        assert_eq!(lines(&buf)[3..7], [
+            "┌Templates───────┐┌Preview─────────────────────────────────┐",
+            "│service         ││name                      : {{service na│",
+            "│broken          ││port                      : 8080        │",
+            "│                ││                                        │",
+        ]);
+        assert_eq!(lines(&buf)[18], "│Template Mode | Choosing a t││(ESC) to cancel / (UP/DOWN) │");
+        assert_eq!(buf[(1, 4)].bg, Color::Yellow);
+        /* The placeholder value is highlighted, the plain one is not */
+        assert_eq!(buf[(47, 4)].bg, Color::LightMagenta);
+        assert_eq!(buf[(47, 5)].bg, Color::Reset);
+    }
+
+    #[test]
+    fn render_confirm_template() {
+        let mut app = app_templates();
+        app.pairs.insert("name".to_string(), "ratatui".to_string());
+        app.current_screen = CurrentScreen::ConfirmTemplate;
+        let buf = render(&app);
+
+        assert_eq!(lines(&buf)[7..9], [
+            "│           Replace the pairs? (y/n)                       │",
+            "│           \"service\" replaces the 1 pair(s)               │",
+        ]);
+        assert_eq!(lines(&buf)[18], "│Template Mode | Choosing a t││(y) to replace the pairs / (│");
+        assert_eq!(buf[(16, 8)].fg, Color::LightRed);
+    }
+
+    #[test]
+    fn popups_are_centered_both_ways() {
+        let area = centered_rect(60, 25, Rect::new(0, 0, 60, 20));
+        assert_eq!(area, Rect::new(12, 7, 36, 5));
+        /* Off the origin the popup stays inside the given area */
+        assert_eq!(centered_rect(50, 50, Rect::new(10, 4, 40, 20)), Rect::new(20, 9, 20, 10));
+    }
+
+    #[test]
+    fn render_broken_template() {
+        let mut app = app_templates();
+        app.template_selected = Some(1);
+        let buf = render(&app);
+
+        assert_eq!(lines(&buf)[4], "│service         ││Invalid template: template is not a JSON│");
+        assert_eq!(lines(&buf)[5], "│broken          ││object                                  │");
+    }
+
+    #[test]
+    fn render_no_templates() {
+        let mut app = App::new();
+        app.templates_dir = "missing".into();
+        app.current_screen = CurrentScreen::Templates;
+        let buf = render(&app);
+
+        assert_eq!(lines(&buf)[4], "│                ││No templates in missing                 │");
+    }
+
+    #[test]
+    fn render_placeholders_left() {
+        let mut app = App::new();
+        app.pairs = templates::parse_template(r#"{"name": "{{service name}}", "owner": "{{team}}", "port": "80"}"#).unwrap();
+        let buf = render(&app);
+
+        assert_eq!(lines(&buf)[1], "│Create New JSON - 2 placeholder(s) to fill                │");
+        assert_eq!(buf[(16, 1)].bg, Color::LightMagenta);
+        assert_eq!(buf[(28, 3)].bg, Color::LightMagenta);
+        assert_eq!(buf[(28, 5)].bg, Color::Reset);
+    }
+
+    fn app_importing() -> App {
+        let mut app = App::new();
+        app.pairs.insert("name".to_string(), "ratatui".to_string());
+        app.pairs.insert("port".to_string(), "8080".to_string());
+        app.start_import();
+        app.import_path = "pairs.env".to_string();
+        app
+    }
+
+    #[test]
+    fn render_import() {
+        let mut app = app_importing();
+        let buf = render(&app);
+
+        assert_eq!(lines(&buf)[7..11], [
+            "            Import .env / key=value / CSV                   ",
+            "             ┌File path (or paste)────────────┐             ",
+            "             │pairs.env                       │             ",
+            "             └────────────────────────────────┘             ",
+        ]);
+        assert_eq!(lines(&buf)[18], "│Import Mode | Waiting for a ││(ESC) to cancel / (ENTER) to│");
+
+        /* Errors replace the popup title */
+        app.import_error = Some("line 2: missing '='".to_string());
+        let buf = render(&app);
+        assert_eq!(lines(&buf)[7], "            line 2: missing '='                             ");
+        assert_eq!(buf[(12, 7)].fg, Color::LightRed);
+    }
+
+    #[test]
+    fn render_import_preview_marks_conflicts() {
+        let mut app = app_importing();
+        app.import_pasted("name=ratatui\nport=9090\nhost=localhost");
+        let buf = render(&app);
+
+        assert_eq!(lines(&buf)[3..9], [
+            "┌Import preview: 3 pairs, 1 conflicts──────────────────────┐",
+            "│Key               Value                  Status           │",
+            "│name              ratatui                unchanged        │",
+            "│port              9090                   replaces: 8080   │",
+            "│host              localhost              new              │",
+            "│                                                          │",
+        ]);
+        assert_eq!(lines(&buf)[18], "│Import Mode | Reviewing the ││(y) to merge, replacing conf│");
+        assert_eq!(buf[(1, 5)].fg, Color::DarkGray);
+        assert_eq!(buf[(1, 6)].fg, Color::LightRed);
+        assert_eq!(buf[(1, 7)].fg, Color::Green);
+    }
+}